@@ -0,0 +1,411 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::storage::SaveData;
+
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActionState>()
+            .init_resource::<Rebinding>()
+            .init_resource::<SwappedBinding>()
+            .add_systems(
+                PreUpdate,
+                (update_action_state, capture_rebinding)
+                    .chain()
+                    .after(InputSystem),
+            );
+    }
+}
+
+/// Something the player can do, independent of the device used to do it.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Action {
+    /// hold to pan the camera with the mouse or right stick
+    Pan,
+    /// hold to orbit the camera with the mouse or right stick
+    Orbit,
+    /// hold to zoom the camera with the mouse or right stick
+    Zoom,
+    /// select the next ship and follow it
+    NextShip,
+    ShipForward,
+    ShipBack,
+    ShipLeft,
+    ShipRight,
+    /// switch between the orbit and the top-down camera
+    SwitchCamera,
+    /// top-down camera panning
+    CameraForward,
+    CameraBack,
+    CameraLeft,
+    CameraRight,
+    /// top-down camera quarter turns
+    RotateLeft,
+    RotateRight,
+    /// toggle the orthographic map view
+    TacticalView,
+    /// switch ships between following their routes and physics steering
+    ToggleMotion,
+    /// show the currents and winds
+    FlowOverlay,
+    /// open and close the settings menu
+    Settings,
+}
+
+impl Action {
+    pub const ALL: [Action; 19] = [
+        Action::Pan,
+        Action::Orbit,
+        Action::Zoom,
+        Action::NextShip,
+        Action::ShipForward,
+        Action::ShipBack,
+        Action::ShipLeft,
+        Action::ShipRight,
+        Action::SwitchCamera,
+        Action::CameraForward,
+        Action::CameraBack,
+        Action::CameraLeft,
+        Action::CameraRight,
+        Action::RotateLeft,
+        Action::RotateRight,
+        Action::TacticalView,
+        Action::ToggleMotion,
+        Action::FlowOverlay,
+        Action::Settings,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::Pan => "Pan camera",
+            Action::Orbit => "Orbit camera",
+            Action::Zoom => "Zoom camera",
+            Action::NextShip => "Next ship",
+            Action::ShipForward => "Push ship up",
+            Action::ShipBack => "Push ship down",
+            Action::ShipLeft => "Push ship left",
+            Action::ShipRight => "Push ship right",
+            Action::SwitchCamera => "Switch camera",
+            Action::CameraForward => "Scroll up",
+            Action::CameraBack => "Scroll down",
+            Action::CameraLeft => "Scroll left",
+            Action::CameraRight => "Scroll right",
+            Action::RotateLeft => "Turn camera left",
+            Action::RotateRight => "Turn camera right",
+            Action::TacticalView => "Tactical view",
+            Action::ToggleMotion => "Toggle ship physics",
+            Action::FlowOverlay => "Show currents",
+            Action::Settings => "Settings",
+        }
+    }
+}
+
+/// A single button on any device.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InputBinding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButton),
+}
+
+impl InputBinding {
+    fn pressed(
+        &self,
+        keys: &ButtonInput<KeyCode>,
+        mouse: &ButtonInput<MouseButton>,
+        gamepads: &Query<&Gamepad>,
+    ) -> bool {
+        match *self {
+            InputBinding::Key(key) => keys.pressed(key),
+            InputBinding::Mouse(button) => mouse.pressed(button),
+            InputBinding::Gamepad(button) => gamepads.iter().any(|pad| pad.pressed(button)),
+        }
+    }
+
+    fn just_pressed(
+        &self,
+        keys: &ButtonInput<KeyCode>,
+        mouse: &ButtonInput<MouseButton>,
+        gamepads: &Query<&Gamepad>,
+    ) -> bool {
+        match *self {
+            InputBinding::Key(key) => keys.just_pressed(key),
+            InputBinding::Mouse(button) => mouse.just_pressed(button),
+            InputBinding::Gamepad(button) => gamepads.iter().any(|pad| pad.just_pressed(button)),
+        }
+    }
+
+    /// keyboard, mouse and gamepad each keep their own binding when rebinding
+    fn same_device(&self, other: &InputBinding) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+impl fmt::Display for InputBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputBinding::Key(key) => write!(f, "{key:?}"),
+            InputBinding::Mouse(button) => write!(f, "Mouse {button:?}"),
+            InputBinding::Gamepad(button) => write!(f, "Pad {button:?}"),
+        }
+    }
+}
+
+/// The buttons bound to each [`Action`], part of the persisted settings.
+/// Actions missing from a save use their default bindings.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(transparent)]
+pub struct Bindings(BTreeMap<Action, Vec<InputBinding>>);
+
+impl Bindings {
+    pub fn get(&self, action: Action) -> Vec<InputBinding> {
+        self.0
+            .get(&action)
+            .cloned()
+            .unwrap_or_else(|| default_bindings(action))
+    }
+
+    /// Replaces the binding of the same device, or adds one for a new device.
+    /// A button can only do one thing: if another action had it, that action
+    /// gets the replaced binding instead and is returned.
+    pub fn rebind(&mut self, action: Action, binding: InputBinding) -> Option<Action> {
+        let mut bindings = self.get(action);
+        let replaced = bindings.iter().position(|b| b.same_device(&binding));
+        let old = replaced.map(|i| bindings[i]);
+
+        let taken_from = Action::ALL
+            .into_iter()
+            .find(|other| *other != action && self.get(*other).contains(&binding));
+        if let Some(other) = taken_from {
+            let mut other_bindings = self.get(other);
+            other_bindings.retain(|b| *b != binding);
+            if let Some(old) = old {
+                other_bindings.push(old);
+            }
+            self.0.insert(other, other_bindings);
+        }
+
+        match replaced {
+            Some(i) => bindings[i] = binding,
+            None => bindings.push(binding),
+        }
+        self.0.insert(action, bindings);
+        taken_from
+    }
+
+    pub fn reset(&mut self) {
+        self.0.clear();
+    }
+}
+
+fn default_bindings(action: Action) -> Vec<InputBinding> {
+    use InputBinding::*;
+    match action {
+        Action::Pan => vec![
+            Key(KeyCode::ControlLeft),
+            Mouse(MouseButton::Middle),
+            Gamepad(GamepadButton::LeftTrigger),
+        ],
+        Action::Orbit => vec![
+            Key(KeyCode::AltLeft),
+            Mouse(MouseButton::Right),
+            Gamepad(GamepadButton::RightTrigger),
+        ],
+        Action::Zoom => vec![
+            Key(KeyCode::ShiftLeft),
+            Gamepad(GamepadButton::LeftTrigger2),
+        ],
+        Action::NextShip => vec![Key(KeyCode::Tab), Gamepad(GamepadButton::South)],
+        Action::ShipForward => vec![Key(KeyCode::ArrowUp), Gamepad(GamepadButton::DPadUp)],
+        Action::ShipBack => vec![Key(KeyCode::ArrowDown), Gamepad(GamepadButton::DPadDown)],
+        Action::ShipLeft => vec![Key(KeyCode::ArrowLeft), Gamepad(GamepadButton::DPadLeft)],
+        Action::ShipRight => vec![Key(KeyCode::ArrowRight), Gamepad(GamepadButton::DPadRight)],
+        Action::SwitchCamera => vec![Key(KeyCode::KeyC), Gamepad(GamepadButton::Select)],
+        Action::CameraForward => vec![Key(KeyCode::KeyW)],
+        Action::CameraBack => vec![Key(KeyCode::KeyS)],
+        Action::CameraLeft => vec![Key(KeyCode::KeyA)],
+        Action::CameraRight => vec![Key(KeyCode::KeyD)],
+        Action::RotateLeft => vec![Key(KeyCode::KeyQ), Gamepad(GamepadButton::West)],
+        Action::RotateRight => vec![Key(KeyCode::KeyE), Gamepad(GamepadButton::East)],
+        Action::TacticalView => vec![Key(KeyCode::KeyT), Gamepad(GamepadButton::North)],
+        Action::ToggleMotion => vec![Key(KeyCode::KeyM)],
+        Action::FlowOverlay => vec![Key(KeyCode::F3)],
+        Action::Settings => vec![Key(KeyCode::Escape), Gamepad(GamepadButton::Start)],
+    }
+}
+
+/// Which actions are active this frame. Systems read this instead of
+/// the raw keyboard, mouse and gamepad input.
+#[derive(Resource, Default, Debug)]
+pub struct ActionState {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+    /// right stick of any gamepad, moves the camera like the mouse does
+    pub look: Vec2,
+    /// left stick of any gamepad, pushes the selected ship
+    pub steer: Vec2,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    pub fn any_just_pressed(&self) -> bool {
+        !self.just_pressed.is_empty()
+    }
+
+    /// -1..1 on both axes, combining the ship buttons with the left stick
+    pub fn steer_axis(&self) -> Vec2 {
+        let buttons = self.axis(Action::ShipLeft, Action::ShipRight, Action::ShipBack, Action::ShipForward);
+        (buttons + self.steer).clamp(Vec2::NEG_ONE, Vec2::ONE)
+    }
+
+    /// -1..1 on both axes, combining the camera scroll buttons with the right stick
+    pub fn scroll_axis(&self) -> Vec2 {
+        let buttons = self.axis(Action::CameraLeft, Action::CameraRight, Action::CameraBack, Action::CameraForward);
+        (buttons + self.look).clamp(Vec2::NEG_ONE, Vec2::ONE)
+    }
+
+    fn axis(&self, left: Action, right: Action, down: Action, up: Action) -> Vec2 {
+        let value = |negative, positive| {
+            self.pressed(positive) as i32 as f32 - self.pressed(negative) as i32 as f32
+        };
+        Vec2::new(value(left, right), value(down, up))
+    }
+}
+
+/// The action waiting for the next button press to become its new binding.
+#[derive(Resource, Default, Debug)]
+pub struct Rebinding(pub Option<Action>);
+
+/// The last rebind took its button from another action, which got the
+/// old binding in exchange. Shown in the settings menu.
+#[derive(Resource, Default, Debug)]
+pub struct SwappedBinding(pub Option<(InputBinding, Action)>);
+
+/// below this the sticks count as centered
+const STICK_DEAD_ZONE: f32 = 0.15;
+
+fn dead_zone(stick: Vec2) -> Vec2 {
+    if stick.length() < STICK_DEAD_ZONE {
+        Vec2::ZERO
+    } else {
+        stick
+    }
+}
+
+fn update_action_state(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    save: Res<SaveData>,
+    rebinding: Res<Rebinding>,
+    mut state: ResMut<ActionState>,
+) {
+    *state = ActionState::default();
+    // the button pressed to rebind must not also trigger its old action
+    if rebinding.0.is_some() {
+        return;
+    }
+
+    for action in Action::ALL {
+        let bindings = save.settings.bindings.get(action);
+        if bindings.iter().any(|b| b.pressed(&keys, &mouse, &gamepads)) {
+            state.pressed.insert(action);
+        }
+        if bindings.iter().any(|b| b.just_pressed(&keys, &mouse, &gamepads)) {
+            state.just_pressed.insert(action);
+        }
+    }
+
+    for gamepad in gamepads.iter() {
+        state.look += dead_zone(gamepad.right_stick());
+        state.steer += dead_zone(gamepad.left_stick());
+    }
+}
+
+/// binds the first button pressed while [`Rebinding`] is waiting,
+/// escape always cancels and can't be bound to anything else
+fn capture_rebinding(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    mut rebinding: ResMut<Rebinding>,
+    mut swapped: ResMut<SwappedBinding>,
+    mut save: ResMut<SaveData>,
+) {
+    let Some(action) = rebinding.0 else {
+        return;
+    };
+
+    let pressed = keys
+        .get_just_pressed()
+        .find(|key| **key != KeyCode::Escape)
+        .map(|key| InputBinding::Key(*key))
+        .or_else(|| {
+            // left click drives the menus and ship dragging, so it can't be rebound
+            mouse
+                .get_just_pressed()
+                .find(|button| **button != MouseButton::Left)
+                .map(|button| InputBinding::Mouse(*button))
+        })
+        .or_else(|| {
+            gamepads
+                .iter()
+                .find_map(|pad| pad.get_just_pressed().next().copied())
+                .map(InputBinding::Gamepad)
+        });
+
+    if let Some(binding) = pressed {
+        swapped.0 = save
+            .settings
+            .bindings
+            .rebind(action, binding)
+            .map(|other| (binding, other));
+        rebinding.0 = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rebind_swaps_with_the_action_that_had_the_button() {
+        let mut bindings = Bindings::default();
+        let taken = bindings.rebind(Action::NextShip, InputBinding::Key(KeyCode::KeyT));
+
+        assert_eq!(taken, Some(Action::TacticalView));
+        assert!(bindings.get(Action::NextShip).contains(&InputBinding::Key(KeyCode::KeyT)));
+        assert!(!bindings.get(Action::NextShip).contains(&InputBinding::Key(KeyCode::Tab)));
+        assert!(bindings.get(Action::TacticalView).contains(&InputBinding::Key(KeyCode::Tab)));
+        assert!(!bindings.get(Action::TacticalView).contains(&InputBinding::Key(KeyCode::KeyT)));
+    }
+
+    #[test]
+    fn rebind_to_a_free_button_takes_nothing() {
+        let mut bindings = Bindings::default();
+        assert_eq!(bindings.rebind(Action::NextShip, InputBinding::Key(KeyCode::KeyN)), None);
+        assert_eq!(bindings.get(Action::NextShip)[0], InputBinding::Key(KeyCode::KeyN));
+    }
+
+    #[test]
+    fn default_bindings_do_not_overlap() {
+        let bindings = Bindings::default();
+        let mut seen = HashSet::new();
+        for action in Action::ALL {
+            for binding in bindings.get(action) {
+                assert!(seen.insert(binding), "{binding} is bound twice");
+            }
+        }
+    }
+}
@@ -1,29 +1,133 @@
 use bevy::prelude::*;
+use crate::actions::{Action, ActionState};
+use crate::map::{MAP_SIZE, WATER_HEIGHT};
+use crate::shake::CameraShake;
+use crate::GameState;
 
 use bevy::input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel};
+use bevy::input::touch::Touches;
+use bevy::picking::focus::HoverMap;
+use bevy::picking::pointer::PointerId;
+use bevy::utils::HashSet;
+use bevy::window::PrimaryWindow;
 
 pub struct PanCameraPlugin;
 
 impl Plugin for PanCameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, spawn_camera)
+        app.init_resource::<CameraMode>()
+            .add_systems(Startup, spawn_camera)
+            .add_systems(OnEnter(GameState::Game), reset_camera)
             .add_systems(
             Update,
-            pan_orbit_camera.run_if(any_with_component::<PanOrbitState>),
+            (
+                switch_camera_mode.in_set(CameraInputSet),
+                follow_target,
+                pan_orbit_camera
+                    .in_set(CameraInputSet)
+                    .run_if(resource_equals(CameraMode::Orbit)),
+                top_down_camera
+                    .in_set(CameraInputSet)
+                    .run_if(resource_equals(CameraMode::TopDown)),
+                touch_camera.in_set(CameraInputSet),
+                ease_camera_limits,
+                update_camera_transform,
+            )
+                .chain()
+                .in_set(CameraSet)
+                .run_if(any_with_component::<PanOrbitState>),
         );
     }
 }
 
+/// All systems that place the main camera from its [`PanOrbitState`].
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CameraSet;
+
+/// The part of [`CameraSet`] that reads player input.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CameraInputSet;
+
 // Bundle to spawn our custom camera easily
 #[derive(Bundle, Default)]
 pub struct PanOrbitCameraBundle {
     pub camera: Camera3dBundle,
     pub state: PanOrbitState,
     pub settings: PanOrbitSettings,
+    pub follow: CameraFollow,
+    pub limits: PanOrbitLimits,
+    pub top_down: TopDownSettings,
+    pub views: CameraViews,
 }
 
-// The internal state of the pan-orbit controller
+/// Which controller drives the main camera, switched with [`Action::SwitchCamera`].
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CameraMode {
+    /// free orbit around `PanOrbitState::center`
+    #[default]
+    Orbit,
+    /// fixed angle from above, scrolled like a strategy game
+    TopDown,
+}
+
+/// The view of the mode that isn't in use, so switching back returns to it.
+/// The active mode works directly on [`PanOrbitState`].
+#[derive(Component, Default)]
+pub struct CameraViews {
+    orbit: Option<PanOrbitState>,
+    top_down: Option<PanOrbitState>,
+}
+
+/// The configuration of the top-down camera
 #[derive(Component)]
+pub struct TopDownSettings {
+    /// fixed pitch, straight down is -90 degrees
+    pub pitch: f32,
+    /// pixels from the window border where the cursor scrolls the view
+    pub edge_margin: f32,
+    /// share of the radius scrolled per second
+    pub scroll_speed: f32,
+    /// Exponent per line of scrolling
+    pub zoom_sensitivity: f32,
+    /// how quickly quarter turns and the pitch settle, see `smooth_nudge`
+    pub turn_rate: f32,
+    /// current rotation, in quarter turns from the starting yaw
+    pub quarter_turns: i32,
+}
+
+impl Default for TopDownSettings {
+    fn default() -> Self {
+        TopDownSettings {
+            pitch: -60.0f32.to_radians(),
+            edge_margin: 16.0,
+            scroll_speed: 1.2,
+            zoom_sensitivity: 0.15,
+            turn_rate: 10.0,
+            quarter_turns: 0,
+        }
+    }
+}
+
+impl TopDownSettings {
+    fn yaw(&self) -> f32 {
+        45.0f32.to_radians() + self.quarter_turns as f32 * FRAC_PI_2
+    }
+
+    /// the top-down view above the spot `state` is looking at
+    fn view_from(&mut self, state: &PanOrbitState) -> PanOrbitState {
+        self.quarter_turns = ((state.yaw - 45.0f32.to_radians()) / FRAC_PI_2).round() as i32;
+        PanOrbitState {
+            center: state.center,
+            radius: state.radius,
+            upside_down: false,
+            pitch: self.pitch,
+            yaw: self.yaw(),
+        }
+    }
+}
+
+// The internal state of the pan-orbit controller
+#[derive(Component, Clone)]
 pub struct PanOrbitState {
     pub center: Vec3,
     pub radius: f32,
@@ -41,12 +145,8 @@ pub struct PanOrbitSettings {
     pub orbit_sensitivity: f32,
     /// Exponent per pixel of mouse motion
     pub zoom_sensitivity: f32,
-    /// Key to hold for panning
-    pub pan_key: Option<KeyCode>,
-    /// Key to hold for orbiting
-    pub orbit_key: Option<KeyCode>,
-    /// Key to hold for zooming
-    pub zoom_key: Option<KeyCode>,
+    /// Pixels of mouse motion per second a fully tilted gamepad stick makes
+    pub stick_sensitivity: f32,
     /// What action is bound to the scroll wheel?
     pub scroll_action: Option<PanOrbitAction>,
     /// For devices with a notched scroll wheel, like desktop mice
@@ -55,6 +155,152 @@ pub struct PanOrbitSettings {
     pub scroll_pixel_sensitivity: f32,
 }
 
+/// Eases the pan-orbit camera towards a ship and keeps it there,
+/// any manual camera input ends following.
+#[derive(Component)]
+pub struct CameraFollow {
+    pub target: Option<Entity>,
+    /// radius to ease to, `None` keeps the current radius
+    pub radius: Option<f32>,
+    /// yaw to ease to, `None` keeps the current yaw
+    pub yaw: Option<f32>,
+    /// pitch to ease to, `None` keeps the current pitch
+    pub pitch: Option<f32>,
+    /// how quickly the camera catches up, see `smooth_nudge`
+    pub decay_rate: f32,
+}
+
+impl Default for CameraFollow {
+    fn default() -> Self {
+        CameraFollow {
+            target: None,
+            radius: None,
+            yaw: None,
+            pitch: None,
+            decay_rate: 3.0,
+        }
+    }
+}
+
+impl CameraFollow {
+    /// follow `target` without changing the view angle
+    pub fn track(target: Entity) -> Self {
+        CameraFollow {
+            target: Some(target),
+            ..default()
+        }
+    }
+}
+
+/// How far the pan-orbit camera may go. Going past a limit is allowed
+/// for a moment, the camera then eases back inside.
+#[derive(Component)]
+pub struct PanOrbitLimits {
+    pub min_radius: f32,
+    pub max_radius: f32,
+    /// most downward pitch, looking straight down is -90 degrees
+    pub min_pitch: f32,
+    /// most upward pitch, further limited so the camera stays above the water
+    pub max_pitch: f32,
+    /// how far above `WATER_HEIGHT` the camera has to stay
+    pub water_clearance: f32,
+    pub center_min: Vec3,
+    pub center_max: Vec3,
+    /// how quickly the camera eases back, see `smooth_nudge`
+    pub ease_rate: f32,
+}
+
+impl Default for PanOrbitLimits {
+    fn default() -> Self {
+        let half = MAP_SIZE as f32 / 2.0;
+        PanOrbitLimits {
+            min_radius: 8.0,
+            max_radius: 300.0,
+            min_pitch: -89.0f32.to_radians(),
+            max_pitch: -5.0f32.to_radians(),
+            water_clearance: 2.0,
+            center_min: Vec3::new(-half, WATER_HEIGHT, -half),
+            center_max: Vec3::new(half, WATER_HEIGHT + 50.0, half),
+            ease_rate: 8.0,
+        }
+    }
+}
+
+impl PanOrbitLimits {
+    /// the closest state to `state` that is within the limits
+    fn clamp(&self, state: &PanOrbitState) -> (Vec3, f32, f32) {
+        let center = state.center.clamp(self.center_min, self.center_max);
+        let radius = state.radius.clamp(self.min_radius, self.max_radius);
+
+        // the camera sits at `center.y - sin(pitch) * radius`
+        let headroom = center.y - WATER_HEIGHT - self.water_clearance;
+        let above_water = (headroom / radius).clamp(-1.0, 1.0).asin();
+        let max_pitch = self.max_pitch.min(above_water).max(self.min_pitch);
+        let pitch = state.pitch.clamp(self.min_pitch, max_pitch);
+
+        (center, radius, pitch)
+    }
+}
+
+/// Entities that take one-finger drags for themselves, like ships getting
+/// a new route drawn with the finger, instead of the drag panning the camera.
+#[derive(Component, Default)]
+pub struct TouchDraggable;
+
+/// What the fingers on the screen did since the last frame.
+#[derive(Debug, Default, PartialEq)]
+pub struct TouchGesture {
+    /// one-finger drag over open water, in pixels
+    pub pan: Vec2,
+    /// how much closer the fingers came, above 1 zooms in
+    pub pinch: f32,
+    /// radians the two fingers turned around each other
+    pub twist: f32,
+}
+
+impl TouchGesture {
+    /// Reads the gesture from `touches`, ignoring the touches in `grabbed`.
+    /// Taps are left to picking, which turns them into clicks.
+    pub fn read(touches: &Touches, grabbed: &HashSet<u64>) -> Self {
+        let fingers: Vec<_> = touches
+            .iter()
+            .filter(|touch| !grabbed.contains(&touch.id()))
+            .collect();
+
+        match fingers.as_slice() {
+            [finger] => TouchGesture {
+                pan: finger.delta(),
+                pinch: 1.0,
+                twist: 0.0,
+            },
+            [a, b, ..] => {
+                let before = a.previous_position() - b.previous_position();
+                let now = a.position() - b.position();
+                if before.length() < 1.0 || now.length() < 1.0 {
+                    return TouchGesture::none();
+                }
+                TouchGesture {
+                    pan: Vec2::ZERO,
+                    pinch: now.length() / before.length(),
+                    twist: before.angle_to(now),
+                }
+            }
+            [] => TouchGesture::none(),
+        }
+    }
+
+    fn none() -> Self {
+        TouchGesture {
+            pinch: 1.0,
+            ..default()
+        }
+    }
+
+    fn is_none(&self) -> bool {
+        *self == TouchGesture::none()
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PanOrbitAction {
     Pan,
@@ -80,9 +326,7 @@ impl Default for PanOrbitSettings {
             pan_sensitivity: 0.001,                 // 1000 pixels per world unit
             orbit_sensitivity: 0.1f32.to_radians(), // 0.1 degree per pixel
             zoom_sensitivity: 0.01,
-            pan_key: Some(KeyCode::ControlLeft),
-            orbit_key: Some(KeyCode::AltLeft),
-            zoom_key: Some(KeyCode::ShiftLeft),
+            stick_sensitivity: 600.0,
             scroll_action: Some(PanOrbitAction::Zoom),
             scroll_line_sensitivity: 16.0, // 1 "line" == 16 "pixels of motion"
             scroll_pixel_sensitivity: 1.0,
@@ -90,24 +334,143 @@ impl Default for PanOrbitSettings {
     }
 }
 
+impl PanOrbitState {
+    /// Where the camera looks at the start of every session.
+    pub fn initial() -> Self {
+        PanOrbitState {
+            center: Vec3::new(1.0, 2.0, 3.0),
+            radius: 50.0,
+            pitch: -45.0f32.to_radians(),
+            yaw: 45.0f32.to_radians(),
+            ..default()
+        }
+    }
+
+    /// where the camera ends up for this state
+    pub fn transform(&self) -> Transform {
+        // YXZ Euler Rotation performs yaw/pitch/roll.
+        let mut transform =
+            Transform::from_rotation(Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, 0.0));
+        // To position the camera, get the backward direction vector
+        // and place the camera at the desired radius from the center.
+        transform.translation = self.center + transform.back() * self.radius;
+        transform
+    }
+}
+
 fn spawn_camera(mut commands: Commands) {
     let mut camera = PanOrbitCameraBundle::default();
     // Position our camera using our component,
     // not Transform (it would get overwritten)
-    camera.state.center = Vec3::new(1.0, 2.0, 3.0);
-    camera.state.radius = 50.0;
-    camera.state.pitch = -45.0f32.to_radians();
-    camera.state.yaw = 45.0f32.to_radians();
-    commands.spawn((camera));
+    camera.state = PanOrbitState::initial();
+    // the minimap camera renders later, UI must still go to the main camera
+    commands.spawn((camera, IsDefaultUiCamera, CameraShake::default()));
+}
+
+/// puts the camera back to where it started, undoing pans and crash zooms,
+/// in whichever mode the player picked
+fn reset_camera(
+    mode: Res<CameraMode>,
+    mut camera: Single<(
+        &mut PanOrbitState,
+        &mut CameraFollow,
+        &mut TopDownSettings,
+        &mut CameraViews,
+    )>,
+) {
+    let (state, follow, top_down, views) = &mut *camera;
+    **state = match *mode {
+        CameraMode::Orbit => PanOrbitState::initial(),
+        CameraMode::TopDown => top_down.view_from(&PanOrbitState::initial()),
+    };
+    **follow = CameraFollow::default();
+    **views = CameraViews::default();
+}
+
+/// swaps the live view with the stored view of the other mode
+fn switch_camera_mode(
+    actions: Res<ActionState>,
+    mut mode: ResMut<CameraMode>,
+    mut camera: Single<(&mut PanOrbitState, &mut TopDownSettings, &mut CameraViews)>,
+) {
+    if !actions.just_pressed(Action::SwitchCamera) {
+        return;
+    }
+    let (state, top_down, views) = &mut *camera;
+    let current = state.clone();
+    match *mode {
+        CameraMode::Orbit => {
+            views.orbit = Some(current);
+            **state = views.top_down.take().unwrap_or_else(|| top_down.view_from(state));
+            *mode = CameraMode::TopDown;
+        }
+        CameraMode::TopDown => {
+            views.top_down = Some(current);
+            if let Some(orbit) = views.orbit.take() {
+                **state = orbit;
+            }
+            *mode = CameraMode::Orbit;
+        }
+    }
 }
 
-use std::f32::consts::{FRAC_PI_2, PI, TAU};
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI, TAU};
+
+/// moves `angle` towards `target` the short way around the circle
+fn nudge_angle(angle: &mut f32, target: f32, decay_rate: f32, delta: f32) {
+    let diff = (target - *angle + PI).rem_euclid(TAU) - PI;
+    *angle += diff * (1.0 - (-decay_rate * delta).exp());
+}
+
+fn follow_target(
+    time: Res<Time>,
+    mut q_camera: Query<(&mut PanOrbitState, &mut CameraFollow, Option<&PanOrbitLimits>)>,
+    targets: Query<&GlobalTransform>,
+) {
+    let delta = time.delta_secs();
+    for (mut state, mut follow, limits) in &mut q_camera {
+        let Some(target) = follow.target else {
+            continue;
+        };
+        // the ship is gone, stay where we are
+        let Ok(target) = targets.get(target) else {
+            follow.target = None;
+            continue;
+        };
+
+        // aim inside the limits, ships float below the lowest center the limits allow
+        let mut center = target.translation();
+        let mut radius = follow.radius;
+        if let Some(limits) = limits {
+            center = center.clamp(limits.center_min, limits.center_max);
+            radius = radius.map(|r| r.clamp(limits.min_radius, limits.max_radius));
+        }
+
+        let rate = follow.decay_rate;
+        state.center.smooth_nudge(&center, rate, delta);
+        if let Some(radius) = radius {
+            state.radius.smooth_nudge(&radius, rate, delta);
+        }
+        if let Some(yaw) = follow.yaw {
+            nudge_angle(&mut state.yaw, yaw, rate, delta);
+        }
+        if let Some(pitch) = follow.pitch {
+            nudge_angle(&mut state.pitch, pitch, rate, delta);
+        }
+    }
+}
 
 fn pan_orbit_camera(
-    kbd: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    actions: Res<ActionState>,
     mut evr_motion: EventReader<MouseMotion>,
     mut evr_scroll: EventReader<MouseWheel>,
-    mut q_camera: Query<(&PanOrbitSettings, &mut PanOrbitState, &mut Transform)>,
+    mut q_camera: Query<(
+        &PanOrbitSettings,
+        &mut PanOrbitState,
+        &Transform,
+        Option<&mut CameraFollow>,
+    )>,
 ) {
     // First, accumulate the total amount of
     // mouse motion and scroll, from all pending events:
@@ -132,17 +495,16 @@ fn pan_orbit_camera(
         }
     }
 
-    for (settings, mut state, mut transform) in &mut q_camera {
+    for (settings, mut state, transform, follow) in &mut q_camera {
+        // The right stick moves like the mouse, it already points Y-Up
+        let total_motion = total_motion + actions.look * settings.stick_sensitivity * time.delta_secs();
+
         // Check how much of each thing we need to apply.
         // Accumulate values from motion and scroll,
         // based on our configuration settings.
 
         let mut total_pan = Vec2::ZERO;
-        if settings
-            .pan_key
-            .map(|key| kbd.pressed(key))
-            .unwrap_or(false)
-        {
+        if actions.pressed(Action::Pan) {
             total_pan -= total_motion * settings.pan_sensitivity;
         }
         if settings.scroll_action == Some(PanOrbitAction::Pan) {
@@ -153,11 +515,7 @@ fn pan_orbit_camera(
         }
 
         let mut total_orbit = Vec2::ZERO;
-        if settings
-            .orbit_key
-            .map(|key| kbd.pressed(key))
-            .unwrap_or(false)
-        {
+        if actions.pressed(Action::Orbit) {
             total_orbit -= total_motion * settings.orbit_sensitivity;
         }
         if settings.scroll_action == Some(PanOrbitAction::Orbit) {
@@ -169,11 +527,7 @@ fn pan_orbit_camera(
         }
 
         let mut total_zoom = Vec2::ZERO;
-        if settings
-            .zoom_key
-            .map(|key| kbd.pressed(key))
-            .unwrap_or(false)
-        {
+        if actions.pressed(Action::Zoom) {
             total_zoom -= total_motion * settings.zoom_sensitivity;
         }
         if settings.scroll_action == Some(PanOrbitAction::Zoom) {
@@ -185,11 +539,7 @@ fn pan_orbit_camera(
 
         // Upon starting a new orbit maneuver (key is just pressed),
         // check if we are starting it upside-down
-        if settings
-            .orbit_key
-            .map(|key| kbd.just_pressed(key))
-            .unwrap_or(false)
-        {
+        if actions.just_pressed(Action::Orbit) {
             state.upside_down = state.pitch < -FRAC_PI_2 || state.pitch > FRAC_PI_2;
         }
 
@@ -247,16 +597,368 @@ fn pan_orbit_camera(
             state.center += transform.up() * total_pan.y * radius;
         }
 
-        // Finally, compute the new camera transform.
-        // (if we changed anything, or if the pan-orbit
-        // controller was just added and thus we are running
-        // for the first time and need to initialize)
-        if any || state.is_added() {
-            // YXZ Euler Rotation performs yaw/pitch/roll.
-            transform.rotation = Quat::from_euler(EulerRot::YXZ, state.yaw, state.pitch, 0.0);
-            // To position the camera, get the backward direction vector
-            // and place the camera at the desired radius from the center.
-            transform.translation = state.center + transform.back() * state.radius;
+        // The player took over, stop following.
+        if any {
+            if let Some(mut follow) = follow {
+                follow.target = None;
+            }
+        }
+    }
+}
+
+/// edge scrolling, scroll buttons, wheel zoom toward the cursor and quarter turns
+fn top_down_camera(
+    time: Res<Time>,
+    actions: Res<ActionState>,
+    mut evr_scroll: EventReader<MouseWheel>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut q_camera: Query<(
+        &Camera,
+        &GlobalTransform,
+        &mut TopDownSettings,
+        &mut PanOrbitState,
+        Option<&mut CameraFollow>,
+    )>,
+) {
+    let delta = time.delta_secs();
+    let scroll_lines: f32 = evr_scroll
+        .read()
+        .map(|ev| match ev.unit {
+            MouseScrollUnit::Line => ev.y,
+            MouseScrollUnit::Pixel => ev.y / 16.0,
+        })
+        .sum();
+
+    // the cursor only scrolls while it is over our window
+    let cursor = window.cursor_position().filter(|_| window.focused);
+    let size = window.size();
+
+    for (camera, camera_transform, mut settings, mut state, follow) in &mut q_camera {
+        let mut any = false;
+
+        if actions.just_pressed(Action::RotateLeft) {
+            settings.quarter_turns -= 1;
+        }
+        if actions.just_pressed(Action::RotateRight) {
+            settings.quarter_turns += 1;
+        }
+        // a crash close-up picks its own pitch
+        let close_up = follow.as_ref().is_some_and(|f| f.target.is_some() && f.pitch.is_some());
+        let (yaw, pitch, rate) = (settings.yaw(), settings.pitch, settings.turn_rate);
+        let current = state.bypass_change_detection();
+        let turning = ((yaw - current.yaw + PI).rem_euclid(TAU) - PI).abs() > 1e-4;
+        let tilting = (current.pitch - pitch).abs() > 1e-4;
+        if turning {
+            nudge_angle(&mut state.yaw, yaw, rate, delta);
+        }
+        if !close_up && tilting {
+            nudge_angle(&mut state.pitch, pitch, rate, delta);
+        }
+
+        let mut edge = Vec2::ZERO;
+        if let Some(cursor) = cursor {
+            // window y points down, scrolling up means towards the top border
+            let margin = settings.edge_margin;
+            edge.x = (cursor.x > size.x - margin) as i32 as f32 - (cursor.x < margin) as i32 as f32;
+            edge.y = (cursor.y < margin) as i32 as f32 - (cursor.y > size.y - margin) as i32 as f32;
         }
+        let scroll = (actions.scroll_axis() + edge).clamp(Vec2::NEG_ONE, Vec2::ONE);
+        if scroll != Vec2::ZERO {
+            any = true;
+            let rotation = Quat::from_rotation_y(state.yaw);
+            let right = rotation * Vec3::X;
+            let forward = rotation * Vec3::NEG_Z;
+            let speed = settings.scroll_speed * state.radius * delta;
+            state.center += (right * scroll.x + forward * scroll.y) * speed;
+        }
+
+        if scroll_lines != 0.0 {
+            any = true;
+            let factor = (-scroll_lines * settings.zoom_sensitivity).exp();
+            // scale the view around the point under the cursor, so it stays there
+            let anchor = cursor
+                .and_then(|c| camera.viewport_to_world(camera_transform, c).ok())
+                .and_then(|ray| {
+                    let origin = Vec3::new(0.0, state.center.y, 0.0);
+                    let distance = ray.intersect_plane(origin, InfinitePlane3d::new(Vec3::Y))?;
+                    Some(ray.get_point(distance))
+                })
+                .unwrap_or(state.center);
+            state.center = anchor + (state.center - anchor) * factor;
+            state.radius *= factor;
+        }
+
+        if any {
+            if let Some(mut follow) = follow {
+                follow.target = None;
+            }
+        }
+    }
+}
+
+/// drag over water pans, pinch zooms and twist orbits around the center,
+/// top-down only turns in quarter turns once the twist passes half of one
+fn touch_camera(
+    touches: Res<Touches>,
+    hover_map: Res<HoverMap>,
+    mode: Res<CameraMode>,
+    draggable: Query<(), With<TouchDraggable>>,
+    parents: Query<&Parent>,
+    mut grabbed: Local<HashSet<u64>>,
+    mut twisted: Local<f32>,
+    mut q_camera: Query<(
+        &PanOrbitSettings,
+        &mut PanOrbitState,
+        &Transform,
+        Option<&mut TopDownSettings>,
+        Option<&mut CameraFollow>,
+    )>,
+) {
+    // a finger that lands on a ship draws its new route for as long as it is down
+    for touch in touches.iter_just_pressed() {
+        let on_draggable = hover_map
+            .get(&PointerId::Touch(touch.id()))
+            .is_some_and(|hits| {
+                hits.keys().any(|hit| {
+                    std::iter::once(*hit)
+                        .chain(parents.iter_ancestors(*hit))
+                        .any(|entity| draggable.contains(entity))
+                })
+            });
+        if on_draggable {
+            grabbed.insert(touch.id());
+        }
+    }
+    grabbed.retain(|id| touches.get_pressed(*id).is_some());
+
+    let gesture = TouchGesture::read(&touches, &grabbed);
+    if touches.iter().filter(|touch| !grabbed.contains(&touch.id())).count() < 2 {
+        *twisted = 0.0;
+    }
+    if gesture.is_none() {
+        return;
+    }
+
+    let mut quarter_turns = 0;
+    if *mode == CameraMode::TopDown {
+        *twisted += gesture.twist;
+        if twisted.abs() > FRAC_PI_4 {
+            quarter_turns = -twisted.signum() as i32;
+            *twisted -= twisted.signum() * FRAC_PI_2;
+        }
+    }
+
+    for (settings, mut state, transform, top_down, follow) in &mut q_camera {
+        // the water follows the finger, touch positions are Y-Down
+        let pan = Vec2::new(-gesture.pan.x, gesture.pan.y) * settings.pan_sensitivity;
+        let radius = state.radius;
+        state.center += transform.right() * pan.x * radius;
+        state.center += transform.up() * pan.y * radius;
+
+        state.radius /= gesture.pinch;
+        match top_down {
+            // top_down_camera turns the view to the new quarter
+            Some(mut top_down) if *mode == CameraMode::TopDown => top_down.quarter_turns += quarter_turns,
+            _ => {
+                state.yaw -= gesture.twist;
+                state.yaw = (state.yaw + PI).rem_euclid(TAU) - PI;
+            }
+        }
+
+        if let Some(mut follow) = follow {
+            follow.target = None;
+        }
+    }
+}
+
+/// pulls a camera that went past its [`PanOrbitLimits`] softly back inside
+fn ease_camera_limits(
+    time: Res<Time>,
+    mut q_camera: Query<(&PanOrbitLimits, &mut PanOrbitState)>,
+) {
+    let delta = time.delta_secs();
+    for (limits, mut state) in &mut q_camera {
+        let (center, radius, pitch) = limits.clamp(state.bypass_change_detection());
+        // only touch the state when needed, so the transform isn't rebuilt every frame
+        if center == state.center && radius == state.radius && pitch == state.pitch {
+            continue;
+        }
+
+        let rate = limits.ease_rate;
+        state.center.smooth_nudge(&center, rate, delta);
+        state.radius.smooth_nudge(&radius, rate, delta);
+        state.pitch.smooth_nudge(&pitch, rate, delta);
+
+        // settle exactly on the limit once close enough
+        if state.center.distance_squared(center) < 1e-4 {
+            state.center = center;
+        }
+        if (state.radius - radius).abs() < 1e-2 {
+            state.radius = radius;
+        }
+        if (state.pitch - pitch).abs() < 1e-4 {
+            state.pitch = pitch;
+        }
+    }
+}
+
+fn update_camera_transform(
+    mut q_camera: Query<(&PanOrbitState, &mut Transform), Changed<PanOrbitState>>,
+) {
+    // Finally, compute the new camera transform.
+    // (if we changed anything, or if the pan-orbit
+    // controller was just added or changed by another system
+    // and thus we need to initialize)
+    for (state, mut transform) in &mut q_camera {
+        let target = state.transform();
+        transform.rotation = target.rotation;
+        transform.translation = target.translation;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::input::touch::{touch_screen_input_system, TouchInput, TouchPhase};
+
+    fn touch(phase: TouchPhase, id: u64, position: Vec2) -> TouchInput {
+        TouchInput {
+            phase,
+            position,
+            window: Entity::PLACEHOLDER,
+            force: None,
+            id,
+        }
+    }
+
+    fn touch_app() -> App {
+        let mut app = App::new();
+        app.add_event::<TouchInput>()
+            .init_resource::<Touches>()
+            .init_resource::<HoverMap>()
+            .init_resource::<CameraMode>()
+            .add_systems(PreUpdate, touch_screen_input_system)
+            .add_systems(Update, touch_camera);
+        app.world_mut().spawn((
+            PanOrbitSettings::default(),
+            PanOrbitState::initial(),
+            Transform::default(),
+            TopDownSettings::default(),
+        ));
+        app
+    }
+
+    /// sends the fingers as one frame of touch events and runs the app
+    fn frame(app: &mut App, phase: TouchPhase, fingers: &[(u64, Vec2)]) {
+        for (id, position) in fingers {
+            app.world_mut().send_event(touch(phase, *id, *position));
+        }
+        app.update();
+    }
+
+    fn gesture(app: &App) -> TouchGesture {
+        TouchGesture::read(app.world().resource::<Touches>(), &HashSet::new())
+    }
+
+    fn camera_state(app: &mut App) -> PanOrbitState {
+        app.world_mut().query::<&PanOrbitState>().single(app.world()).clone()
+    }
+
+    #[test]
+    fn one_finger_drag_pans() {
+        let mut app = touch_app();
+        let before = camera_state(&mut app);
+        frame(&mut app, TouchPhase::Started, &[(0, Vec2::new(100.0, 100.0))]);
+        frame(&mut app, TouchPhase::Moved, &[(0, Vec2::new(110.0, 95.0))]);
+
+        let read = gesture(&app);
+        assert_eq!(read.pan, Vec2::new(10.0, -5.0));
+        assert_eq!(read.pinch, 1.0);
+        assert_eq!(read.twist, 0.0);
+
+        // the water follows the finger, so the center moves the other way
+        let after = camera_state(&mut app);
+        let sensitivity = PanOrbitSettings::default().pan_sensitivity;
+        let expected = before.center + Vec3::new(-10.0, -5.0, 0.0) * sensitivity * before.radius;
+        assert!(after.center.distance(expected) < 1e-4, "{} != {expected}", after.center);
+        assert_eq!(after.radius, before.radius);
+        assert!((after.yaw - before.yaw).abs() < 1e-5);
+    }
+
+    #[test]
+    fn spreading_two_fingers_zooms_in() {
+        let mut app = touch_app();
+        let before = camera_state(&mut app);
+        frame(
+            &mut app,
+            TouchPhase::Started,
+            &[(0, Vec2::new(100.0, 100.0)), (1, Vec2::new(200.0, 100.0))],
+        );
+        frame(
+            &mut app,
+            TouchPhase::Moved,
+            &[(0, Vec2::new(50.0, 100.0)), (1, Vec2::new(250.0, 100.0))],
+        );
+
+        let read = gesture(&app);
+        assert_eq!(read.pan, Vec2::ZERO);
+        assert!((read.pinch - 2.0).abs() < 1e-5);
+        assert!(read.twist.abs() < 1e-5);
+
+        let after = camera_state(&mut app);
+        assert!((after.radius - before.radius / 2.0).abs() < 1e-4);
+        assert_eq!(after.center, before.center);
+    }
+
+    #[test]
+    fn twisting_two_fingers_orbits() {
+        let mut app = touch_app();
+        let before = camera_state(&mut app);
+        frame(
+            &mut app,
+            TouchPhase::Started,
+            &[(0, Vec2::new(100.0, 100.0)), (1, Vec2::new(200.0, 100.0))],
+        );
+        // a quarter turn around the midpoint
+        frame(
+            &mut app,
+            TouchPhase::Moved,
+            &[(0, Vec2::new(150.0, 50.0)), (1, Vec2::new(150.0, 150.0))],
+        );
+
+        let read = gesture(&app);
+        assert!((read.pinch - 1.0).abs() < 1e-5);
+        assert!((read.twist - FRAC_PI_2).abs() < 1e-5);
+
+        let after = camera_state(&mut app);
+        let expected = (before.yaw - FRAC_PI_2 + PI).rem_euclid(TAU) - PI;
+        assert!((after.yaw - expected).abs() < 1e-4);
+    }
+
+    #[test]
+    fn top_down_twist_snaps_to_quarter_turns() {
+        let mut app = touch_app();
+        app.insert_resource(CameraMode::TopDown);
+        let before = camera_state(&mut app);
+        frame(
+            &mut app,
+            TouchPhase::Started,
+            &[(0, Vec2::new(100.0, 100.0)), (1, Vec2::new(200.0, 100.0))],
+        );
+
+        // a small twist does nothing yet
+        let small = Vec2::from_angle(0.3) * 50.0;
+        let center = Vec2::new(150.0, 100.0);
+        frame(&mut app, TouchPhase::Moved, &[(0, center - small), (1, center + small)]);
+        let turns = app.world_mut().query::<&TopDownSettings>().single(app.world()).quarter_turns;
+        assert_eq!(turns, 0);
+        assert_eq!(camera_state(&mut app).yaw, before.yaw);
+
+        // going on past half a quarter turn snaps to the next quarter
+        let large = Vec2::from_angle(0.9) * 50.0;
+        frame(&mut app, TouchPhase::Moved, &[(0, center - large), (1, center + large)]);
+        let turns = app.world_mut().query::<&TopDownSettings>().single(app.world()).quarter_turns;
+        assert_eq!(turns, -1);
+        assert_eq!(camera_state(&mut app).yaw, before.yaw);
     }
 }
@@ -1,4 +1,7 @@
 use bevy::prelude::*;
+use crate::difficulty::Difficulty;
+use crate::ship::PathFollow;
+use crate::weather::Weather;
 use crate::GameState;
 
 
@@ -9,9 +12,27 @@ pub struct Collider {
     pub(crate) rect: Rect,
 }
 
+impl Collider {
+    /// the collider's rect on the xz plane when its entity is at `pos`
+    pub fn world_rect(&self, pos: Vec3) -> Rect {
+        Rect::from_corners(pos.xz(), pos.xz() + self.rect.max)
+    }
+}
+
+/// A collider that stays put, like a wreck. Obstacles only collide with ships.
+#[derive(Component)]
+pub struct Obstacle;
+
+/// Entities with this component are ignored by collision checks until the timer runs out.
+#[derive(Component)]
+pub struct Invulnerable(pub Timer);
+
 impl Plugin for CollisionPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (check_collision).run_if(in_state(GameState::Game)))
+        app.add_systems(Update, (tick_invulnerable, check_collision).chain().run_if(in_state(GameState::Game)))
+        .add_systems(Update, forecast_conflicts.run_if(in_state(GameState::Game)))
+        .add_systems(OnEnter(GameState::Game), clear_collision_events)
+        .init_resource::<ConflictForecast>()
         .add_event::<CollisionEvent>();
     }
 }
@@ -22,20 +43,65 @@ pub struct CollisionEvent {
     pub entity_b: Entity,
 }
 
+/// seconds ahead that routes are checked for conflicts
+pub const FORECAST_HORIZON: f32 = 10.0;
+const FORECAST_STEPS: usize = 20;
+
+/// Two ships that will come too close if both stay on their routes.
+#[derive(Debug, Clone, Copy)]
+pub struct Conflict {
+    pub entity_a: Entity,
+    pub entity_b: Entity,
+    /// seconds until they meet
+    pub in_secs: f32,
+    /// where they meet
+    pub point: Vec3,
+}
+
+/// Conflicts predicted from the ships' routes, soonest first.
+#[derive(Resource, Default, Debug)]
+pub struct ConflictForecast {
+    pub conflicts: Vec<Conflict>,
+}
+
+impl ConflictForecast {
+    /// the conflict that happens first, if any
+    pub fn soonest(&self) -> Option<&Conflict> {
+        self.conflicts.first()
+    }
+}
+
+/// drops collisions that were still queued when the last session ended
+fn clear_collision_events(mut events: ResMut<Events<CollisionEvent>>) {
+    events.clear();
+}
+
+fn tick_invulnerable(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Invulnerable)>,
+) {
+    for (entity, mut invulnerable) in query.iter_mut() {
+        if invulnerable.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Invulnerable>();
+        }
+    }
+}
+
 fn check_collision(
-    mut query: Query<(Entity, &GlobalTransform, &Collider)>,
+    mut query: Query<(Entity, &GlobalTransform, &Collider, Has<Obstacle>), Without<Invulnerable>>,
     mut commands: Commands,
     mut collision_event_writer: EventWriter<CollisionEvent>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
-    for (entity_a, transform_a, mut collider_a) in query.iter() {
-        for (entity_b, transform_b, mut collider_b) in query.iter() {
-            if (entity_a == entity_b) {
+    for (entity_a, transform_a, mut collider_a, obstacle_a) in query.iter() {
+        for (entity_b, transform_b, mut collider_b, obstacle_b) in query.iter() {
+            if (entity_a == entity_b) || (obstacle_a && obstacle_b) {
                 continue;
             }
 
-            let rect_a_pos = Rect::from_corners(transform_a.translation().xz(), transform_a.translation().xz() + collider_a.rect.max);
-            let rect_b_pos = Rect::from_corners(transform_b.translation().xz(), transform_b.translation().xz() + collider_b.rect.max);
+            let rect_a_pos = collider_a.world_rect(transform_a.translation());
+            let rect_b_pos = collider_b.world_rect(transform_b.translation());
             if !rect_a_pos.intersect(rect_b_pos).is_empty() {
                 // next_state.set(GameState::GameOver);
                 collision_event_writer.send(CollisionEvent {entity_a, entity_b});
@@ -43,3 +109,50 @@ fn check_collision(
         }
     }
 }
+
+/// Steps every ship along its route and records the first moment two of them
+/// come closer than their colliders allow.
+fn forecast_conflicts(
+    difficulty: Res<Difficulty>,
+    weather: Res<Weather>,
+    ships: Query<(Entity, &PathFollow, &Collider)>,
+    mut forecast: ResMut<ConflictForecast>,
+) {
+    let speed = difficulty.ship_speed * weather.profile.ship_speed;
+    let step_secs = FORECAST_HORIZON / FORECAST_STEPS as f32;
+
+    let routes: Vec<(Entity, f32, Vec<Vec3>)> = ships
+        .iter()
+        .map(|(entity, path_follow, collider)| {
+            let points = (1..=FORECAST_STEPS)
+                .map(|step| {
+                    let t = path_follow.t + speed * step_secs * step as f32;
+                    path_follow.curve.position(t) + path_follow.offset
+                })
+                .collect();
+            (entity, collider.rect.size().length() / 2.0, points)
+        })
+        .collect();
+
+    forecast.conflicts.clear();
+    for (i, (entity_a, size_a, points_a)) in routes.iter().enumerate() {
+        for (entity_b, size_b, points_b) in routes.iter().skip(i + 1) {
+            let clearance = size_a + size_b;
+            let meeting = points_a
+                .iter()
+                .zip(points_b)
+                .position(|(a, b)| a.xz().distance(b.xz()) < clearance);
+            if let Some(step) = meeting {
+                forecast.conflicts.push(Conflict {
+                    entity_a: *entity_a,
+                    entity_b: *entity_b,
+                    in_secs: step_secs * (step + 1) as f32,
+                    point: points_a[step].lerp(points_b[step], 0.5),
+                });
+            }
+        }
+    }
+    forecast
+        .conflicts
+        .sort_by(|a, b| a.in_secs.total_cmp(&b.in_secs));
+}
@@ -0,0 +1,168 @@
+use std::f32::consts::PI;
+use bevy::color::palettes::css::{GREEN, MIDNIGHT_BLUE, RED, WHITE, WHITE_SMOKE};
+use bevy::pbr::light_consts;
+use bevy::prelude::*;
+use crate::config::ConfigAssets;
+use crate::level::{CurrentLevel, LevelList};
+use crate::weather::Weather;
+use crate::GameState;
+
+pub struct DayNightPlugin;
+
+impl Plugin for DayNightPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimeOfDay>()
+            .add_systems(Startup, spawn_sun)
+            .add_systems(OnEnter(GameState::Game), reset_time_of_day)
+            .add_systems(
+                Update,
+                (advance_time_of_day, (move_sun, switch_nav_lights))
+                    .chain()
+                    .run_if(in_state(GameState::Game)),
+            );
+    }
+}
+
+/// ambient brightness at noon and at midnight
+const AMBIENT_DAY: f32 = 80.0;
+const AMBIENT_NIGHT: f32 = 8.0;
+/// sun illuminance at noon
+const SUN_ILLUMINANCE: f32 = light_consts::lux::OVERCAST_DAY * 2.0;
+/// below this much daylight ships turn their navigation lights on
+const DUSK: f32 = 0.25;
+
+/// The clock of the running session.
+#[derive(Resource, Debug)]
+pub struct TimeOfDay {
+    /// 0..24
+    pub hour: f32,
+    /// real seconds one full day takes
+    pub day_length: f32,
+}
+
+impl Default for TimeOfDay {
+    fn default() -> Self {
+        Self {
+            hour: 10.0,
+            day_length: 240.0,
+        }
+    }
+}
+
+impl TimeOfDay {
+    /// height of the sun, 0 at sunrise and sunset, 1 at noon, negative at night
+    pub fn sun_elevation(&self) -> f32 {
+        ((self.hour - 6.0) / 12.0 * PI).sin()
+    }
+
+    /// 0 at night, 1 at noon
+    pub fn daylight(&self) -> f32 {
+        self.sun_elevation().max(0.0)
+    }
+
+    pub fn is_night(&self) -> bool {
+        self.daylight() < DUSK
+    }
+}
+
+#[derive(Component)]
+struct Sun;
+
+/// A ship light that is only switched on between dusk and dawn.
+#[derive(Component)]
+pub struct NavLight {
+    pub intensity: f32,
+}
+
+impl NavLight {
+    /// red light on the left side
+    pub fn port() -> (NavLight, PointLight) {
+        Self::colored(RED.into())
+    }
+
+    /// green light on the right side
+    pub fn starboard() -> (NavLight, PointLight) {
+        Self::colored(GREEN.into())
+    }
+
+    /// white light on top of the mast
+    pub fn masthead() -> (NavLight, PointLight) {
+        Self::colored(WHITE.into())
+    }
+
+    fn colored(color: Color) -> (NavLight, PointLight) {
+        (
+            NavLight { intensity: 60_000.0 },
+            PointLight {
+                color,
+                intensity: 0.0,
+                range: 12.0,
+                shadows_enabled: false,
+                ..default()
+            },
+        )
+    }
+}
+
+fn spawn_sun(mut commands: Commands) {
+    commands.spawn((
+        Sun,
+        DirectionalLight {
+            shadows_enabled: true,
+            illuminance: SUN_ILLUMINANCE,
+            ..default()
+        },
+        Transform::default(),
+    ));
+}
+
+fn reset_time_of_day(
+    mut commands: Commands,
+    config: Res<ConfigAssets>,
+    level_lists: Res<Assets<LevelList>>,
+    current_level: Res<CurrentLevel>,
+) {
+    let time_of_day = current_level
+        .get(&config, &level_lists)
+        .map(|level| TimeOfDay {
+            hour: level.start_hour,
+            day_length: level.day_length,
+        })
+        .unwrap_or_default();
+    commands.insert_resource(time_of_day);
+}
+
+fn advance_time_of_day(time: Res<Time>, mut time_of_day: ResMut<TimeOfDay>) {
+    let hours = 24.0 * time.delta_secs() / time_of_day.day_length.max(1.0);
+    time_of_day.hour = (time_of_day.hour + hours).rem_euclid(24.0);
+}
+
+fn move_sun(
+    time_of_day: Res<TimeOfDay>,
+    weather: Res<Weather>,
+    mut ambient: ResMut<AmbientLight>,
+    mut sun: Single<(&mut Transform, &mut DirectionalLight), With<Sun>>,
+) {
+    let (transform, light) = &mut *sun;
+
+    // the sun travels from east to west, its forward points down while it is up
+    let angle = (time_of_day.hour - 6.0) / 12.0 * PI;
+    transform.rotation = Quat::from_rotation_y(-PI / 2.0) * Quat::from_rotation_x(-angle);
+    light.illuminance = SUN_ILLUMINANCE * time_of_day.daylight() * weather.profile.ambient;
+
+    let daylight = time_of_day.daylight();
+    ambient.brightness = AMBIENT_NIGHT.lerp(AMBIENT_DAY, daylight) * weather.profile.ambient;
+    ambient.color = Color::from(MIDNIGHT_BLUE).mix(&WHITE_SMOKE.into(), daylight);
+}
+
+fn switch_nav_lights(
+    time: Res<Time>,
+    time_of_day: Res<TimeOfDay>,
+    mut lights: Query<(&NavLight, &mut PointLight)>,
+) {
+    let on = time_of_day.is_night();
+    for (nav_light, mut light) in lights.iter_mut() {
+        let target = if on { nav_light.intensity } else { 0.0 };
+        light.intensity.smooth_nudge(&target, 2.0, time.delta_secs());
+    }
+}
@@ -0,0 +1,214 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+use crate::config::{ConfigAssets, RonAssetPlugin};
+use crate::ship::CrashReplay;
+use crate::GameState;
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RonAssetPlugin::<DifficultyCurve>::new(&["difficulty.ron"]))
+            .init_resource::<Difficulty>()
+            .add_systems(OnEnter(GameState::Game), reset_difficulty)
+            .add_systems(
+                Update,
+                update_difficulty
+                    .in_set(DifficultySet)
+                    .run_if(in_state(GameState::Game))
+                    // frozen with the score while the crash replays
+                    .run_if(not(resource_exists::<CrashReplay>)),
+            );
+    }
+}
+
+/// Systems that advance [`Difficulty`]; everything reading it should run after this.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DifficultySet;
+
+/// How the session gets harder, loaded from `assets/config/*.difficulty.ron`.
+#[derive(Asset, TypePath, Deserialize, Clone, Debug)]
+pub struct DifficultyCurve {
+    pub progression: Progression,
+    /// seconds between two spawns
+    pub spawn_interval: Ramp,
+    /// how many ships may be on the map at once
+    pub max_ships: Ramp,
+    /// route progress per second, see `move_ship`
+    pub ship_speed: Ramp,
+    /// chance that a new ship is a cargo ship instead of a sail boat
+    pub cargo_chance: Ramp,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub enum Progression {
+    /// difficulty rises continuously and peaks after `ramp_secs`, 0 starts at the peak
+    Time { ramp_secs: f32 },
+    /// difficulty rises in steps every `wave_secs` and peaks at wave `max_wave`,
+    /// 0 for either starts at the peak
+    Waves { wave_secs: f32, max_wave: u32 },
+}
+
+/// A value that goes from `start` at difficulty 0 to `end` at difficulty 1.
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct Ramp {
+    pub start: f32,
+    pub end: f32,
+}
+
+impl Ramp {
+    pub fn at(&self, level: f32) -> f32 {
+        self.start.lerp(self.end, level)
+    }
+}
+
+/// The difficulty of the running session, derived from the [`DifficultyCurve`].
+#[derive(Resource, Debug)]
+pub struct Difficulty {
+    /// seconds since the session started
+    pub elapsed: f32,
+    /// 0 for a fresh session, 1 once the curve has peaked
+    pub level: f32,
+    pub wave: u32,
+    pub spawn_interval: f32,
+    pub max_ships: u32,
+    pub ship_speed: f32,
+    pub cargo_chance: f32,
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Self {
+            elapsed: 0.0,
+            level: 0.0,
+            wave: 0,
+            spawn_interval: 10.0,
+            max_ships: 10,
+            ship_speed: 0.1,
+            cargo_chance: 0.5,
+        }
+    }
+}
+
+impl Difficulty {
+    pub fn from_curve(curve: &DifficultyCurve, elapsed: f32) -> Self {
+        let (wave, level) = match curve.progression {
+            Progression::Time { ramp_secs } if ramp_secs <= 0.0 => (0, 1.0),
+            Progression::Time { ramp_secs } => (0, elapsed / ramp_secs),
+            Progression::Waves { wave_secs, max_wave } if wave_secs <= 0.0 || max_wave == 0 => {
+                (max_wave, 1.0)
+            }
+            Progression::Waves { wave_secs, max_wave } => {
+                let wave = (elapsed / wave_secs) as u32;
+                (wave, wave as f32 / max_wave as f32)
+            }
+        };
+        let level = level.clamp(0.0, 1.0);
+
+        Self {
+            elapsed,
+            level,
+            wave,
+            spawn_interval: curve.spawn_interval.at(level).max(0.1),
+            max_ships: curve.max_ships.at(level).round() as u32,
+            ship_speed: curve.ship_speed.at(level),
+            cargo_chance: curve.cargo_chance.at(level).clamp(0.0, 1.0),
+        }
+    }
+}
+
+fn reset_difficulty(mut commands: Commands) {
+    commands.insert_resource(Difficulty::default());
+}
+
+fn update_difficulty(
+    time: Res<Time>,
+    config: Res<ConfigAssets>,
+    curves: Res<Assets<DifficultyCurve>>,
+    mut difficulty: ResMut<Difficulty>,
+) {
+    let elapsed = difficulty.elapsed + time.delta_secs();
+    match curves.get(&config.difficulty) {
+        Some(curve) => *difficulty = Difficulty::from_curve(curve, elapsed),
+        None => difficulty.elapsed = elapsed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn curve(progression: Progression) -> DifficultyCurve {
+        DifficultyCurve {
+            progression,
+            spawn_interval: Ramp { start: 10.0, end: 2.0 },
+            max_ships: Ramp { start: 4.0, end: 12.0 },
+            ship_speed: Ramp { start: 0.1, end: 0.3 },
+            cargo_chance: Ramp { start: 0.2, end: 0.8 },
+        }
+    }
+
+    #[test]
+    fn time_progression_rises_continuously() {
+        let curve = curve(Progression::Time { ramp_secs: 100.0 });
+
+        let start = Difficulty::from_curve(&curve, 0.0);
+        assert_eq!(start.level, 0.0);
+        assert_eq!(start.spawn_interval, 10.0);
+        assert_eq!(start.max_ships, 4);
+
+        let half = Difficulty::from_curve(&curve, 50.0);
+        assert_eq!(half.level, 0.5);
+        assert_eq!(half.wave, 0);
+        assert_eq!(half.spawn_interval, 6.0);
+        assert_eq!(half.max_ships, 8);
+        assert!((half.ship_speed - 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn wave_progression_rises_in_steps() {
+        let curve = curve(Progression::Waves { wave_secs: 30.0, max_wave: 4 });
+
+        let first = Difficulty::from_curve(&curve, 29.0);
+        assert_eq!(first.wave, 0);
+        assert_eq!(first.level, 0.0);
+
+        let second = Difficulty::from_curve(&curve, 30.0);
+        assert_eq!(second.wave, 1);
+        assert_eq!(second.level, 0.25);
+        assert_eq!(Difficulty::from_curve(&curve, 59.0).level, 0.25);
+    }
+
+    #[test]
+    fn difficulty_stays_at_the_peak() {
+        let time = curve(Progression::Time { ramp_secs: 100.0 });
+        let late = Difficulty::from_curve(&time, 1000.0);
+        assert_eq!(late.level, 1.0);
+        assert_eq!(late.spawn_interval, 2.0);
+        assert_eq!(late.max_ships, 12);
+
+        let waves = curve(Progression::Waves { wave_secs: 30.0, max_wave: 4 });
+        let late = Difficulty::from_curve(&waves, 1000.0);
+        assert_eq!(late.wave, 33);
+        assert_eq!(late.level, 1.0);
+        assert!((late.cargo_chance - 0.8).abs() < 1e-6);
+    }
+
+    #[test]
+    fn empty_ramps_start_at_the_peak() {
+        let time = curve(Progression::Time { ramp_secs: 0.0 });
+        assert_eq!(Difficulty::from_curve(&time, 0.0).level, 1.0);
+        assert_eq!(Difficulty::from_curve(&time, 5.0).level, 1.0);
+
+        let no_waves = curve(Progression::Waves { wave_secs: 30.0, max_wave: 0 });
+        let start = Difficulty::from_curve(&no_waves, 0.0);
+        assert_eq!(start.level, 1.0);
+        assert_eq!(start.wave, 0);
+
+        let instant = curve(Progression::Waves { wave_secs: 0.0, max_wave: 4 });
+        let start = Difficulty::from_curve(&instant, 0.0);
+        assert_eq!(start.level, 1.0);
+        assert_eq!(start.wave, 4);
+        assert_eq!(start.spawn_interval, 2.0);
+    }
+}
@@ -0,0 +1,202 @@
+use std::f32::consts::{PI, TAU};
+use bevy::prelude::*;
+use crate::actions::{Action, ActionState};
+use crate::difficulty::{Difficulty, DifficultySet};
+use crate::ship::PathFollow;
+use crate::weather::Weather;
+use crate::GameState;
+
+pub struct DynamicsPlugin;
+
+impl Plugin for DynamicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MotionMode>()
+            .add_systems(Update, toggle_motion_mode.run_if(in_state(GameState::Game)))
+            .add_systems(
+                Update,
+                (steer_ships, integrate_ships)
+                    .chain()
+                    .in_set(ShipMotionSet)
+                    .after(DifficultySet)
+                    .run_if(in_state(GameState::Game))
+                    .run_if(resource_equals(MotionMode::Physics)),
+            );
+    }
+}
+
+/// Systems that move ship roots, whichever [`MotionMode`] is active.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ShipMotionSet;
+
+/// How ships get from one point of their route to the next.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MotionMode {
+    /// ships are glued to their spline
+    #[default]
+    Kinematic,
+    /// ships steer towards their spline with rudder and thrust
+    Physics,
+}
+
+/// Route progress per second the thrust of every ship is tuned for,
+/// faster difficulty speeds scale the thrust up.
+const BASE_ROUTE_SPEED: f32 = 0.1;
+
+/// Physical properties and state of a ship used in [`MotionMode::Physics`].
+#[derive(Component, Clone, Debug)]
+pub struct ShipDynamics {
+    pub mass: f32,
+    /// resistance against moving forward
+    pub drag: f32,
+    /// resistance against sliding sideways, the keel
+    pub lateral_drag: f32,
+    /// forward force at full throttle
+    pub thrust: f32,
+    /// radians, positive turns to port
+    pub rudder_angle: f32,
+    pub max_rudder: f32,
+    /// radians per second the rudder can swing
+    pub rudder_rate: f32,
+    /// turn rate per unit of speed and radian of rudder
+    pub turn_factor: f32,
+    /// route progress ahead of the ship that the controller steers at
+    pub lookahead: f32,
+    pub velocity: Vec3,
+    /// forces added by other systems this frame, cleared after integration
+    pub external_force: Vec3,
+}
+
+impl ShipDynamics {
+    pub fn sail() -> Self {
+        Self {
+            mass: 1.0,
+            drag: 0.8,
+            lateral_drag: 3.0,
+            thrust: 6.0,
+            rudder_angle: 0.0,
+            max_rudder: 35f32.to_radians(),
+            rudder_rate: 90f32.to_radians(),
+            turn_factor: 0.25,
+            lookahead: 0.15,
+            velocity: Vec3::ZERO,
+            external_force: Vec3::ZERO,
+        }
+    }
+
+    pub fn cargo() -> Self {
+        Self {
+            mass: 4.0,
+            drag: 3.0,
+            lateral_drag: 10.0,
+            thrust: 20.0,
+            max_rudder: 25f32.to_radians(),
+            rudder_rate: 30f32.to_radians(),
+            turn_factor: 0.12,
+            lookahead: 0.25,
+            ..Self::sail()
+        }
+    }
+
+    pub fn apply_force(&mut self, force: Vec3) {
+        self.external_force += force;
+    }
+
+    /// changes the velocity at once, unlike a force this does not depend on the frame time
+    pub fn apply_impulse(&mut self, impulse: Vec3) {
+        self.velocity += impulse / self.mass;
+        self.velocity.y = 0.0;
+    }
+}
+
+/// yaw of a transform, in the convention of `look_at` (0 faces -z)
+fn heading(transform: &Transform) -> f32 {
+    transform.rotation.to_euler(EulerRot::YXZ).0
+}
+
+fn forward(heading: f32) -> Vec3 {
+    Quat::from_rotation_y(heading) * Vec3::NEG_Z
+}
+
+/// wraps an angle to [-PI, PI]
+fn wrap_angle(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(TAU) - PI
+}
+
+fn toggle_motion_mode(actions: Res<ActionState>, mut mode: ResMut<MotionMode>) {
+    if actions.just_pressed(Action::ToggleMotion) {
+        *mode = match *mode {
+            MotionMode::Kinematic => MotionMode::Physics,
+            MotionMode::Physics => MotionMode::Kinematic,
+        };
+    }
+}
+
+/// Pure pursuit: move the route progress to the closest point ahead of the ship
+/// and turn the rudder towards a point `lookahead` further along the route.
+fn steer_ships(
+    time: Res<Time>,
+    mut query: Query<(&mut PathFollow, &mut ShipDynamics, &Transform)>,
+) {
+    const SEARCH_STEPS: u32 = 10;
+
+    for (mut path_follow, mut dynamics, transform) in query.iter_mut() {
+        let pos = transform.translation;
+        let segments = path_follow.curve.segments().len() as f32;
+
+        let search = dynamics.lookahead * 2.0;
+        let mut best_t = path_follow.t;
+        let mut best_distance = f32::MAX;
+        for step in 0..=SEARCH_STEPS {
+            let t = path_follow.t + search * step as f32 / SEARCH_STEPS as f32;
+            let distance = path_follow.curve.position(t).xz().distance_squared(pos.xz());
+            if distance < best_distance {
+                best_distance = distance;
+                best_t = t;
+            }
+        }
+        path_follow.t = best_t.rem_euclid(segments);
+
+        let target = path_follow.curve.position(path_follow.t + dynamics.lookahead);
+        let to_target = target - pos;
+        let desired = (-to_target.x).atan2(-to_target.z);
+        let error = wrap_angle(desired - heading(transform));
+
+        let rudder_target = error.clamp(-dynamics.max_rudder, dynamics.max_rudder);
+        let max_step = dynamics.rudder_rate * time.delta_secs();
+        dynamics.rudder_angle += (rudder_target - dynamics.rudder_angle).clamp(-max_step, max_step);
+    }
+}
+
+fn integrate_ships(
+    time: Res<Time>,
+    difficulty: Res<Difficulty>,
+    weather: Res<Weather>,
+    mut query: Query<(&mut ShipDynamics, &mut Transform), With<PathFollow>>,
+) {
+    let delta = time.delta_secs();
+    let throttle = difficulty.ship_speed / BASE_ROUTE_SPEED * weather.profile.ship_speed;
+
+    for (mut dynamics, mut transform) in query.iter_mut() {
+        let heading = heading(&transform);
+        let forward = forward(heading);
+        let side = Vec3::Y.cross(forward);
+
+        let forward_speed = dynamics.velocity.dot(forward);
+        let lateral_speed = dynamics.velocity.dot(side);
+
+        let force = forward * dynamics.thrust * throttle
+            - forward * forward_speed * dynamics.drag
+            - side * lateral_speed * dynamics.lateral_drag
+            + dynamics.external_force;
+
+        let acceleration = force / dynamics.mass;
+        dynamics.velocity += acceleration * delta;
+        dynamics.velocity.y = 0.0;
+        dynamics.external_force = Vec3::ZERO;
+
+        // a rudder only turns a ship that moves through the water
+        let turn = dynamics.rudder_angle * dynamics.turn_factor * forward_speed;
+        transform.rotation = Quat::from_rotation_y(wrap_angle(heading + turn * delta));
+        transform.translation += dynamics.velocity * delta;
+    }
+}
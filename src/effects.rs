@@ -0,0 +1,412 @@
+use bevy::ecs::system::SystemParam;
+use bevy::pbr::NotShadowCaster;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use rand::prelude::*;
+use crate::collision::CollisionEvent;
+use crate::map::WATER_HEIGHT;
+use crate::ship::{BowSplash, ShipRoot};
+use crate::GameState;
+
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_effect_pool)
+            .add_systems(OnEnter(GameState::Game), clear_effects)
+            .add_systems(
+                Update,
+                (emit_wakes, emit_bow_splashes, crash_effects, emit_smoke, update_particles)
+                    .chain()
+                    .run_if(in_state(GameState::Game)),
+            )
+            .add_observer(add_wake);
+    }
+}
+
+/// particles that exist at once, when all are in use new ones are dropped
+const POOL_SIZE: usize = 512;
+/// distance behind the ship's center where the foam starts
+const STERN_OFFSET: f32 = 4.0;
+/// foam particles per world unit travelled
+const WAKE_DENSITY: f32 = 1.5;
+/// speed at which the wake reaches its full width
+const WAKE_FULL_SPEED: f32 = 10.0;
+/// seconds before the same ship can splash again
+const SPLASH_COOLDOWN: f32 = 0.4;
+const SPLASH_DROPS: usize = 6;
+const DEBRIS_PIECES: usize = 16;
+/// seconds a wreck keeps smoking, and between two puffs
+const SMOKE_SECS: f32 = 6.0;
+const SMOKE_INTERVAL: f32 = 0.12;
+/// how fast debris goes down once it hits the water
+const SINK_SPEED: f32 = 0.4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EffectKind {
+    Foam,
+    Splash,
+    Smoke,
+    Debris,
+}
+
+/// How a kind of particle moves and grows.
+struct EffectSpec {
+    lifetime: f32,
+    start_size: f32,
+    end_size: f32,
+    gravity: f32,
+    /// fraction of the velocity lost per second
+    drag: f32,
+    /// goes down slowly after hitting the water instead of falling through
+    sinks: bool,
+}
+
+impl EffectKind {
+    fn spec(self) -> EffectSpec {
+        match self {
+            EffectKind::Foam => EffectSpec {
+                lifetime: 2.5,
+                start_size: 0.6,
+                end_size: 1.8,
+                gravity: 0.0,
+                drag: 1.5,
+                sinks: false,
+            },
+            EffectKind::Splash => EffectSpec {
+                lifetime: 0.8,
+                start_size: 0.4,
+                end_size: 0.1,
+                gravity: 9.8,
+                drag: 0.5,
+                sinks: false,
+            },
+            EffectKind::Smoke => EffectSpec {
+                lifetime: 3.0,
+                start_size: 0.8,
+                end_size: 3.5,
+                gravity: -0.5,
+                drag: 0.8,
+                sinks: false,
+            },
+            EffectKind::Debris => EffectSpec {
+                lifetime: 5.0,
+                start_size: 0.35,
+                end_size: 0.2,
+                gravity: 9.8,
+                drag: 0.3,
+                sinks: true,
+            },
+        }
+    }
+}
+
+/// One pooled effect entity, hidden while it is not in use.
+#[derive(Component, Default)]
+struct Particle {
+    active: bool,
+    age: f32,
+    lifetime: f32,
+    velocity: Vec3,
+    start_size: f32,
+    end_size: f32,
+    gravity: f32,
+    drag: f32,
+    sinks: bool,
+}
+
+/// Free particles and the shared materials they are drawn with.
+/// All entities are spawned up front so effects never allocate while playing.
+#[derive(Resource)]
+struct EffectPool {
+    free: Vec<Entity>,
+    foam: Handle<StandardMaterial>,
+    splash: Handle<StandardMaterial>,
+    smoke: Handle<StandardMaterial>,
+    debris: Handle<StandardMaterial>,
+}
+
+impl EffectPool {
+    fn material(&self, kind: EffectKind) -> &Handle<StandardMaterial> {
+        match kind {
+            EffectKind::Foam => &self.foam,
+            EffectKind::Splash => &self.splash,
+            EffectKind::Smoke => &self.smoke,
+            EffectKind::Debris => &self.debris,
+        }
+    }
+}
+
+/// Leaves foam behind its ship, measured from how far it moved.
+#[derive(Component, Default)]
+struct Wake {
+    last_position: Option<Vec3>,
+    /// foam owed from the distance travelled, spawned once it reaches one
+    budget: f32,
+    splash_cooldown: f32,
+}
+
+/// Puffs smoke where two ships crashed until it runs out.
+#[derive(Component)]
+struct SmokeEmitter {
+    remaining: Timer,
+    puff: Timer,
+}
+
+#[derive(SystemParam)]
+struct Effects<'w, 's> {
+    pool: ResMut<'w, EffectPool>,
+    particles: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static mut Particle,
+            &'static mut Transform,
+            &'static mut Visibility,
+            &'static mut MeshMaterial3d<StandardMaterial>,
+        ),
+    >,
+}
+
+impl Effects<'_, '_> {
+    /// takes a free particle from the pool, does nothing if there is none
+    fn emit(&mut self, kind: EffectKind, position: Vec3, velocity: Vec3, size: f32) {
+        let Some(entity) = self.pool.free.pop() else {
+            return;
+        };
+        let Ok((_, mut particle, mut transform, mut visibility, mut material)) = self.particles.get_mut(entity) else {
+            return;
+        };
+        let spec = kind.spec();
+        *particle = Particle {
+            active: true,
+            age: 0.0,
+            lifetime: spec.lifetime,
+            velocity,
+            start_size: spec.start_size * size,
+            end_size: spec.end_size * size,
+            gravity: spec.gravity,
+            drag: spec.drag,
+            sinks: spec.sinks,
+        };
+        *transform = Transform::from_translation(position).with_scale(Vec3::splat(particle.start_size));
+        *visibility = Visibility::Visible;
+        if material.0 != *self.pool.material(kind) {
+            material.0 = self.pool.material(kind).clone();
+        }
+    }
+}
+
+fn translucent(color: Color) -> StandardMaterial {
+    StandardMaterial {
+        base_color: color,
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    }
+}
+
+fn spawn_effect_pool(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mesh = meshes.add(Sphere::new(0.5).mesh().ico(1).unwrap());
+    let foam = materials.add(translucent(Color::srgba(0.95, 0.97, 1.0, 0.6)));
+    let free = (0..POOL_SIZE)
+        .map(|_| {
+            commands
+                .spawn((
+                    Name::new("Particle"),
+                    Particle::default(),
+                    Mesh3d(mesh.clone()),
+                    MeshMaterial3d(foam.clone()),
+                    Transform::default(),
+                    Visibility::Hidden,
+                    NotShadowCaster,
+                    PickingBehavior::IGNORE,
+                ))
+                .id()
+        })
+        .collect();
+
+    commands.insert_resource(EffectPool {
+        free,
+        foam,
+        splash: materials.add(translucent(Color::srgba(0.85, 0.93, 1.0, 0.8))),
+        smoke: materials.add(translucent(Color::srgba(0.2, 0.2, 0.2, 0.6))),
+        debris: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.4, 0.28, 0.18),
+            perceptual_roughness: 0.9,
+            ..default()
+        }),
+    });
+}
+
+/// hands every particle back to the pool for a new game
+fn clear_effects(mut effects: Effects) {
+    let Effects { pool, particles } = &mut effects;
+    for (entity, mut particle, _, mut visibility, _) in particles.iter_mut() {
+        if particle.active {
+            particle.active = false;
+            *visibility = Visibility::Hidden;
+            pool.free.push(entity);
+        }
+    }
+}
+
+fn add_wake(trigger: Trigger<OnAdd, ShipRoot>, mut commands: Commands) {
+    commands.entity(trigger.entity()).insert(Wake::default());
+}
+
+/// foam behind moving ships, more and wider the faster they go
+fn emit_wakes(
+    time: Res<Time>,
+    mut effects: Effects,
+    mut ships: Query<(&GlobalTransform, &mut Wake), Without<Particle>>,
+) {
+    let delta = time.delta_secs();
+    if delta <= 0.0 {
+        return;
+    }
+    let mut rng = rand::thread_rng();
+    for (transform, mut wake) in ships.iter_mut() {
+        wake.splash_cooldown = (wake.splash_cooldown - delta).max(0.0);
+        let position = transform.translation();
+        let Some(last) = wake.last_position.replace(position) else {
+            continue;
+        };
+        let travelled = (position - last).with_y(0.0);
+        let distance = travelled.length();
+        if distance < f32::EPSILON {
+            continue;
+        }
+        let direction = travelled / distance;
+        let speed = distance / delta;
+        let width = (speed / WAKE_FULL_SPEED).clamp(0.3, 1.5);
+        let side = direction.cross(Vec3::Y);
+        let stern = (position - direction * STERN_OFFSET).with_y(WATER_HEIGHT + 0.1);
+
+        wake.budget += distance * WAKE_DENSITY;
+        while wake.budget >= 1.0 {
+            wake.budget -= 1.0;
+            let spread = rng.gen_range(-1.0..1.0);
+            let velocity = side * spread * speed * 0.15;
+            effects.emit(EffectKind::Foam, stern + side * spread * 0.5, velocity, width);
+        }
+    }
+}
+
+/// spray thrown up where a bow cuts into a wave
+fn emit_bow_splashes(
+    mut splashes: EventReader<BowSplash>,
+    mut effects: Effects,
+    mut wakes: Query<&mut Wake>,
+) {
+    let mut rng = rand::thread_rng();
+    for splash in splashes.read() {
+        let Ok(mut wake) = wakes.get_mut(splash.ship) else {
+            continue;
+        };
+        if wake.splash_cooldown > 0.0 {
+            continue;
+        }
+        wake.splash_cooldown = SPLASH_COOLDOWN;
+        for _ in 0..SPLASH_DROPS {
+            let velocity = Vec3::new(rng.gen_range(-1.5..1.5), rng.gen_range(3.0..5.0), rng.gen_range(-1.5..1.5));
+            effects.emit(EffectKind::Splash, splash.position, velocity, 1.0);
+        }
+    }
+}
+
+/// debris bursting from the crash site and smoke rising from it, once per
+/// crash even though collisions are reported for both orders of the pair
+fn crash_effects(
+    mut commands: Commands,
+    mut collisions: EventReader<CollisionEvent>,
+    mut effects: Effects,
+    colliders: Query<(&GlobalTransform, Has<ShipRoot>), Without<Particle>>,
+) {
+    let mut rng = rand::thread_rng();
+    let mut crashes = HashSet::new();
+    for ev in collisions.read() {
+        let pair = (ev.entity_a.min(ev.entity_b), ev.entity_a.max(ev.entity_b));
+        if !crashes.insert(pair) {
+            continue;
+        }
+        let Ok([(a, a_ship), (b, b_ship)]) = colliders.get_many([ev.entity_a, ev.entity_b]) else {
+            continue;
+        };
+        // a ship running into a wreck or the coast burns where the ship is
+        let center = match (a_ship, b_ship) {
+            (true, true) => (a.translation() + b.translation()) / 2.0,
+            (true, false) => a.translation(),
+            (false, true) => b.translation(),
+            (false, false) => continue,
+        };
+        for _ in 0..DEBRIS_PIECES {
+            let velocity = Vec3::new(rng.gen_range(-4.0..4.0), rng.gen_range(3.0..7.0), rng.gen_range(-4.0..4.0));
+            effects.emit(EffectKind::Debris, center + Vec3::Y, velocity, rng.gen_range(0.6..1.4));
+        }
+        commands.spawn((
+            SmokeEmitter {
+                remaining: Timer::from_seconds(SMOKE_SECS, TimerMode::Once),
+                puff: Timer::from_seconds(SMOKE_INTERVAL, TimerMode::Repeating),
+            },
+            Transform::from_translation(center + Vec3::Y),
+            StateScoped(GameState::Game),
+        ));
+    }
+}
+
+fn emit_smoke(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut effects: Effects,
+    mut emitters: Query<(Entity, &mut SmokeEmitter, &Transform), Without<Particle>>,
+) {
+    let mut rng = rand::thread_rng();
+    for (entity, mut emitter, transform) in emitters.iter_mut() {
+        if emitter.remaining.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        // thins out as the fire dies down
+        let strength = emitter.remaining.fraction_remaining();
+        for _ in 0..emitter.puff.tick(time.delta()).times_finished_this_tick() {
+            let velocity = Vec3::new(rng.gen_range(-0.5..0.5), rng.gen_range(1.5..2.5), rng.gen_range(-0.5..0.5));
+            effects.emit(EffectKind::Smoke, transform.translation, velocity, 0.5 + strength);
+        }
+    }
+}
+
+/// moves and grows live particles and returns finished ones to the pool
+fn update_particles(time: Res<Time>, mut effects: Effects) {
+    let delta = time.delta_secs();
+    let Effects { pool, particles } = &mut effects;
+    for (entity, mut particle, mut transform, mut visibility, _) in particles.iter_mut() {
+        if !particle.active {
+            continue;
+        }
+        particle.age += delta;
+        if particle.age >= particle.lifetime {
+            particle.active = false;
+            *visibility = Visibility::Hidden;
+            pool.free.push(entity);
+            continue;
+        }
+
+        if particle.sinks && transform.translation.y <= WATER_HEIGHT {
+            particle.velocity = Vec3::new(particle.velocity.x * 0.5, -SINK_SPEED, particle.velocity.z * 0.5);
+        } else {
+            particle.velocity.y -= particle.gravity * delta;
+        }
+        let drag = (1.0 - particle.drag * delta).max(0.0);
+        particle.velocity *= drag;
+        transform.translation += particle.velocity * delta;
+
+        let t = particle.age / particle.lifetime;
+        transform.scale = Vec3::splat(particle.start_size.lerp(particle.end_size, t));
+    }
+}
@@ -0,0 +1,200 @@
+use std::f32::consts::TAU;
+use bevy::prelude::*;
+use crate::actions::{Action, ActionState};
+use crate::dynamics::{MotionMode, ShipDynamics, ShipMotionSet};
+use crate::map::{MAP_SIZE, WATER_HEIGHT};
+use crate::ship::PathFollow;
+use crate::GameState;
+
+pub struct FlowPlugin;
+
+impl Plugin for FlowPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FlowField>()
+            .init_resource::<FlowOverlay>()
+            .add_systems(OnEnter(GameState::Game), reset_flow_field)
+            .add_systems(
+                Update,
+                (update_flow_field, push_ships)
+                    .chain()
+                    .before(ShipMotionSet)
+                    .run_if(in_state(GameState::Game)),
+            )
+            .add_systems(Update, toggle_flow_overlay.run_if(in_state(GameState::Game)))
+            .add_systems(
+                Update,
+                draw_flow_field
+                    .run_if(in_state(GameState::Game))
+                    .run_if(|overlay: Res<FlowOverlay>| overlay.0),
+            );
+    }
+}
+
+/// cells per side of the flow grid
+const FLOW_RESOLUTION: usize = 16;
+/// strongest current in world units per second
+const CURRENT_STRENGTH: f32 = 1.5;
+/// strongest wind in world units per second
+const WIND_STRENGTH: f32 = 3.0;
+/// how quickly a kinematic ship steers back onto its route, per second
+const DRIFT_RECOVERY: f32 = 0.3;
+
+/// Currents and wind over the whole map, sampled on a grid and
+/// recomputed every frame so they change over time.
+#[derive(Resource)]
+pub struct FlowField {
+    elapsed: f32,
+    currents: Vec<Vec2>,
+    winds: Vec<Vec2>,
+}
+
+impl Default for FlowField {
+    fn default() -> Self {
+        let mut field = Self {
+            elapsed: 0.0,
+            currents: vec![Vec2::ZERO; FLOW_RESOLUTION * FLOW_RESOLUTION],
+            winds: vec![Vec2::ZERO; FLOW_RESOLUTION * FLOW_RESOLUTION],
+        };
+        field.recompute();
+        field
+    }
+}
+
+impl FlowField {
+    fn cell_size() -> f32 {
+        MAP_SIZE as f32 / FLOW_RESOLUTION as f32
+    }
+
+    /// world position of the centre of a grid cell
+    fn cell_center(x: usize, z: usize) -> Vec2 {
+        let half = MAP_SIZE as f32 / 2.0;
+        Vec2::new(x as f32 + 0.5, z as f32 + 0.5) * Self::cell_size() - half
+    }
+
+    fn recompute(&mut self) {
+        let t = self.elapsed;
+        for z in 0..FLOW_RESOLUTION {
+            for x in 0..FLOW_RESOLUTION {
+                let p = Self::cell_center(x, z) / MAP_SIZE as f32 * TAU;
+
+                // slow swirls that drift across the map
+                let current = Vec2::new((p.y + t * 0.05).sin(), (p.x - t * 0.04).cos());
+
+                // one prevailing direction that turns slowly, with local gusts
+                let angle = t * 0.02 + 0.4 * (p.x + t * 0.1).sin();
+                let gust = 0.7 + 0.3 * (p.y * 2.0 + t * 0.3).sin();
+                let wind = Vec2::from_angle(angle) * gust;
+
+                self.currents[z * FLOW_RESOLUTION + x] = current * CURRENT_STRENGTH;
+                self.winds[z * FLOW_RESOLUTION + x] = wind * WIND_STRENGTH;
+            }
+        }
+    }
+
+    /// bilinear sample of a grid at a world position
+    fn sample(grid: &[Vec2], pos: Vec3) -> Vec2 {
+        let half = MAP_SIZE as f32 / 2.0;
+        let max = (FLOW_RESOLUTION - 1) as f32;
+        let cell = ((pos.xz() + half) / Self::cell_size() - 0.5).clamp(Vec2::ZERO, Vec2::splat(max));
+        let (x0, z0) = (cell.x.floor() as usize, cell.y.floor() as usize);
+        let (x1, z1) = ((x0 + 1).min(FLOW_RESOLUTION - 1), (z0 + 1).min(FLOW_RESOLUTION - 1));
+        let f = cell - cell.floor();
+
+        let at = |x: usize, z: usize| grid[z * FLOW_RESOLUTION + x];
+        let top = at(x0, z0).lerp(at(x1, z0), f.x);
+        let bottom = at(x0, z1).lerp(at(x1, z1), f.x);
+        top.lerp(bottom, f.y)
+    }
+
+    pub fn current_at(&self, pos: Vec3) -> Vec2 {
+        Self::sample(&self.currents, pos)
+    }
+
+    pub fn wind_at(&self, pos: Vec3) -> Vec2 {
+        Self::sample(&self.winds, pos)
+    }
+}
+
+/// How strongly a ship is carried by wind and current, sail boats catch the
+/// wind while heavy cargo ships mostly go with the current.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct FlowResponse {
+    pub wind: f32,
+    pub current: f32,
+}
+
+impl FlowResponse {
+    pub fn sail() -> Self {
+        Self { wind: 1.0, current: 0.3 }
+    }
+
+    pub fn cargo() -> Self {
+        Self { wind: 0.2, current: 1.0 }
+    }
+}
+
+/// Whether the flow field is drawn as arrows over the water.
+#[derive(Resource, Default)]
+pub struct FlowOverlay(pub bool);
+
+fn reset_flow_field(mut commands: Commands) {
+    commands.insert_resource(FlowField::default());
+}
+
+fn update_flow_field(time: Res<Time>, mut field: ResMut<FlowField>) {
+    field.elapsed += time.delta_secs();
+    field.recompute();
+}
+
+fn push_ships(
+    time: Res<Time>,
+    mode: Res<MotionMode>,
+    field: Res<FlowField>,
+    mut ships: Query<(&FlowResponse, &Transform, &mut PathFollow, &mut ShipDynamics)>,
+) {
+    let delta = time.delta_secs();
+    for (response, transform, mut path_follow, mut dynamics) in ships.iter_mut() {
+        let pos = transform.translation;
+        let flow = field.current_at(pos) * response.current + field.wind_at(pos) * response.wind;
+        let flow = Vec3::new(flow.x, 0.0, flow.y);
+
+        match *mode {
+            MotionMode::Kinematic => {
+                path_follow.offset += flow * delta;
+                path_follow.offset *= (-DRIFT_RECOVERY * delta).exp();
+            }
+            MotionMode::Physics => {
+                // drag balances this force once the ship drifts with the flow
+                let force = flow * dynamics.drag;
+                dynamics.apply_force(force);
+            }
+        }
+    }
+}
+
+fn toggle_flow_overlay(actions: Res<ActionState>, mut overlay: ResMut<FlowOverlay>) {
+    if actions.just_pressed(Action::FlowOverlay) {
+        overlay.0 = !overlay.0;
+    }
+}
+
+fn draw_flow_field(field: Res<FlowField>, mut gizmos: Gizmos) {
+    for z in 0..FLOW_RESOLUTION {
+        for x in 0..FLOW_RESOLUTION {
+            let center = FlowField::cell_center(x, z);
+            let start = Vec3::new(center.x, WATER_HEIGHT + 0.5, center.y);
+            let current = field.currents[z * FLOW_RESOLUTION + x];
+            let wind = field.winds[z * FLOW_RESOLUTION + x];
+            gizmos.arrow(
+                start,
+                start + Vec3::new(current.x, 0.0, current.y) * 2.0,
+                Color::srgba(0.2, 0.4, 1.0, 0.8),
+            );
+            gizmos.arrow(
+                start + Vec3::Y,
+                start + Vec3::Y + Vec3::new(wind.x, 0.0, wind.y) * 2.0,
+                Color::srgba(1.0, 1.0, 1.0, 0.6),
+            );
+        }
+    }
+}
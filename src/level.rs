@@ -0,0 +1,103 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+use crate::config::{ConfigAssets, RonAssetPlugin};
+use crate::storage::SaveData;
+use crate::{GameState, Score};
+use crate::radar::{default_radar_stations, RadarStationDef};
+use crate::rail::CameraRailDef;
+use crate::weather::WeatherChange;
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RonAssetPlugin::<LevelList>::new(&["levels.ron"]))
+            .init_resource::<CurrentLevel>()
+            .add_systems(OnEnter(GameState::GameOver), unlock_next_level);
+    }
+}
+
+/// All playable levels, loaded from `assets/config/*.levels.ron`.
+#[derive(Asset, TypePath, Deserialize, Clone, Debug)]
+pub struct LevelList {
+    pub levels: Vec<Level>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Level {
+    pub id: String,
+    pub name: String,
+    /// fixed weather changes, random weather is rolled if this is empty
+    #[serde(default)]
+    pub weather: Vec<WeatherChange>,
+    /// hour of the day the session starts at, late hours make a night level
+    #[serde(default = "default_start_hour")]
+    pub start_hour: f32,
+    /// real seconds one full day takes
+    #[serde(default = "default_day_length")]
+    pub day_length: f32,
+    #[serde(default = "default_radar_stations")]
+    pub radar_stations: Vec<RadarStationDef>,
+    /// camera flight played when the level starts
+    #[serde(default)]
+    pub intro: Option<CameraRailDef>,
+    /// score needed on the level before this one to unlock it, 0 if it is always open
+    #[serde(default)]
+    pub unlock_score: u32,
+}
+
+impl Level {
+    pub fn is_open(&self, save: &SaveData) -> bool {
+        self.unlock_score == 0 || save.is_unlocked(&self.id)
+    }
+}
+
+fn default_start_hour() -> f32 {
+    10.0
+}
+
+fn default_day_length() -> f32 {
+    240.0
+}
+
+/// Id of the level that the next session is played on, the first level if empty.
+#[derive(Resource, Default, Debug, Clone)]
+pub struct CurrentLevel(pub String);
+
+impl CurrentLevel {
+    /// looks up the selected level in the loaded level list
+    pub fn get<'a>(
+        &self,
+        config: &ConfigAssets,
+        level_lists: &'a Assets<LevelList>,
+    ) -> Option<&'a Level> {
+        let levels = &level_lists.get(&config.levels)?.levels;
+        levels
+            .iter()
+            .find(|level| level.id == self.0)
+            .or_else(|| levels.first())
+    }
+}
+
+/// a good enough score opens the level after the one just played
+fn unlock_next_level(
+    config: Res<ConfigAssets>,
+    level_lists: Res<Assets<LevelList>>,
+    current_level: Res<CurrentLevel>,
+    score: Res<Score>,
+    mut save: ResMut<SaveData>,
+) {
+    let (Some(list), Some(level)) = (level_lists.get(&config.levels), current_level.get(&config, &level_lists)) else {
+        return;
+    };
+    let next = list
+        .levels
+        .iter()
+        .skip_while(|l| l.id != level.id)
+        .nth(1);
+    if let Some(next) = next {
+        if !next.is_open(&save) && score.seconds() >= next.unlock_score {
+            save.unlock_level(&next.id);
+        }
+    }
+}
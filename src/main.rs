@@ -1,8 +1,27 @@
+mod actions;
 mod camera;
 mod map;
 mod ship;
 mod collision;
 mod ui;
+mod storage;
+mod config;
+mod difficulty;
+mod dynamics;
+mod flow;
+mod level;
+mod weather;
+mod daynight;
+mod radar;
+mod minimap;
+mod tactical;
+mod rail;
+mod shake;
+mod sound;
+mod tension;
+mod effects;
+mod wreck;
+mod mode;
 
 use bevy::color::palettes::css::WHITE_SMOKE;
 use crate::ship::ShipAssets;
@@ -12,10 +31,29 @@ use bevy::utils::tracing::Instrument;
 use bevy::pbr::{DirectionalLightShadowMap};
 use bevy_asset_loader::prelude::*;
 use bevy_rand::prelude::*;
+use crate::actions::ActionsPlugin;
 use crate::collision::CollisionPlugin;
+use crate::config::ConfigAssets;
+use crate::daynight::DayNightPlugin;
+use crate::difficulty::DifficultyPlugin;
+use crate::dynamics::DynamicsPlugin;
+use crate::effects::EffectsPlugin;
+use crate::flow::FlowPlugin;
+use crate::level::LevelPlugin;
 use crate::map::{MapAssets, MapPlugin};
-use crate::ship::ShipPlugin;
+use crate::minimap::MinimapPlugin;
+use crate::mode::ModePlugin;
+use crate::radar::RadarPlugin;
+use crate::rail::RailPlugin;
+use crate::shake::ShakePlugin;
+use crate::sound::SoundPlugin;
+use crate::tension::TensionPlugin;
+use crate::ship::{CrashReplay, ShipPlugin};
+use crate::storage::StoragePlugin;
+use crate::tactical::TacticalPlugin;
 use crate::ui::UiPlugin;
+use crate::weather::WeatherPlugin;
+use crate::wreck::WreckPlugin;
 
 #[derive(Clone, Eq, PartialEq, Debug, Hash, Default, States)]
 pub enum GameState {
@@ -26,30 +64,66 @@ pub enum GameState {
     GameOver
 }
 
+/// Time survived in the current session, which is what the player scores on.
+#[derive(Resource, Default)]
+pub struct Score(pub f32);
+
+impl Score {
+    pub fn seconds(&self) -> u32 {
+        self.0 as u32
+    }
+}
+
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
 
         // load
         .init_state::<GameState>()
+        .enable_state_scoped_entities::<GameState>()
         .add_loading_state(
         LoadingState::new(GameState::Loading)
-            .continue_to_state(GameState::Game)
+            .continue_to_state(GameState::Menu)
             .load_collection::<ShipAssets>()
             .load_collection::<MapAssets>()
+            .load_collection::<ConfigAssets>()
         )
         .add_plugins(MeshPickingPlugin)
+        .add_plugins(ActionsPlugin)
         .add_plugins(PanCameraPlugin)
         .add_plugins(MapPlugin)
         .add_plugins(ShipPlugin)
         .add_plugins(CollisionPlugin)
         .add_plugins(UiPlugin)
+        .add_plugins(StoragePlugin)
+        .add_plugins(DifficultyPlugin)
+        .add_plugins(DynamicsPlugin)
+        .add_plugins(FlowPlugin)
+        .add_plugins(LevelPlugin)
+        .add_plugins(WeatherPlugin)
+        .add_plugins(DayNightPlugin)
+        .add_plugins(RadarPlugin)
+        .add_plugins(MinimapPlugin)
+        .add_plugins(TacticalPlugin)
+        .add_plugins(RailPlugin)
+        .add_plugins(ShakePlugin)
+        .add_plugins(SoundPlugin)
+        .add_plugins(TensionPlugin)
+        .add_plugins(EffectsPlugin)
+        .add_plugins(WreckPlugin)
+        .add_plugins(ModePlugin)
 
         .add_plugins(EntropyPlugin::<WyRand>::default())
 
         .insert_resource(DirectionalLightShadowMap { size: 4048 })
 
         .add_systems(OnEnter(GameState::Game), setup)
+        .add_systems(
+            Update,
+            tick_score
+                .run_if(in_state(GameState::Game))
+                .run_if(not(resource_exists::<CrashReplay>)),
+        )
 
         .run();
 }
@@ -60,5 +134,10 @@ fn setup(mut commands: Commands) {
         color: WHITE_SMOKE.into(),
         brightness: 80.,
     });
+    commands.insert_resource(Score::default());
+
+}
 
+fn tick_score(time: Res<Time>, mut score: ResMut<Score>) {
+    score.0 += time.delta_secs();
 }
@@ -0,0 +1,195 @@
+use bevy::prelude::*;
+use bevy::render::camera::{ScalingMode, Viewport};
+use bevy::render::view::RenderLayers;
+use bevy::ui::RelativeCursorPosition;
+use bevy::window::PrimaryWindow;
+use crate::camera::{CameraFollow, PanOrbitState};
+use crate::collision::ConflictForecast;
+use crate::map::{MAP_SIZE, WATER_HEIGHT};
+use crate::radar::{RadarContact, RadarStation};
+use crate::ship::{PathFollow, ShipRoot};
+use crate::GameState;
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_gizmo_group::<MinimapGizmos>()
+            .add_systems(Startup, (spawn_minimap_camera, configure_minimap_gizmos))
+            .add_systems(OnEnter(GameState::Game), (spawn_minimap_frame, activate_minimap))
+            .add_systems(OnExit(GameState::Game), deactivate_minimap)
+            .add_systems(
+                Update,
+                (fit_minimap_viewport, draw_minimap, minimap_click)
+                    .run_if(in_state(GameState::Game)),
+            );
+    }
+}
+
+/// side length of the minimap in logical pixels
+const MINIMAP_SIZE: f32 = 200.0;
+const MINIMAP_MARGIN: f32 = 12.0;
+const MINIMAP_BORDER: f32 = 3.0;
+/// layer that only the minimap camera renders
+const MINIMAP_LAYER: usize = 1;
+
+/// Gizmos that are only drawn on the minimap.
+#[derive(Default, Reflect, GizmoConfigGroup)]
+pub struct MinimapGizmos;
+
+#[derive(Component)]
+struct MinimapCamera;
+
+/// The UI node framing the minimap, clicking it moves the main camera.
+#[derive(Component)]
+struct MinimapFrame;
+
+fn spawn_minimap_camera(mut commands: Commands) {
+    commands.spawn((
+        MinimapCamera,
+        Camera3d::default(),
+        Camera {
+            order: 1,
+            is_active: false,
+            ..default()
+        },
+        Projection::Orthographic(OrthographicProjection {
+            scaling_mode: ScalingMode::Fixed {
+                width: MAP_SIZE as f32,
+                height: MAP_SIZE as f32,
+            },
+            ..OrthographicProjection::default_3d()
+        }),
+        // screen up is -z, so the minimap reads like the map seen from above
+        Transform::from_xyz(0.0, 200.0, 0.0).looking_at(Vec3::ZERO, Vec3::NEG_Z),
+        RenderLayers::from_layers(&[0, MINIMAP_LAYER]),
+    ));
+}
+
+fn configure_minimap_gizmos(mut config_store: ResMut<GizmoConfigStore>) {
+    let (config, _) = config_store.config_mut::<MinimapGizmos>();
+    config.render_layers = RenderLayers::layer(MINIMAP_LAYER);
+    config.line_width = 2.0;
+}
+
+fn spawn_minimap_frame(mut commands: Commands) {
+    commands.spawn((
+        MinimapFrame,
+        StateScoped(GameState::Game),
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(MINIMAP_MARGIN),
+            bottom: Val::Px(MINIMAP_MARGIN),
+            width: Val::Px(MINIMAP_SIZE),
+            height: Val::Px(MINIMAP_SIZE),
+            border: UiRect::all(Val::Px(MINIMAP_BORDER)),
+            ..default()
+        },
+        BorderColor(Color::srgb(0.9, 0.9, 0.9)),
+        Interaction::default(),
+        RelativeCursorPosition::default(),
+    ));
+}
+
+fn activate_minimap(mut camera: Single<&mut Camera, With<MinimapCamera>>) {
+    camera.is_active = true;
+}
+
+fn deactivate_minimap(mut camera: Single<&mut Camera, With<MinimapCamera>>) {
+    camera.is_active = false;
+}
+
+/// keeps the rendered minimap inside its frame when the window is resized
+fn fit_minimap_viewport(
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut camera: Single<&mut Camera, With<MinimapCamera>>,
+) {
+    let scale = window.scale_factor();
+    let inner = MINIMAP_SIZE - 2.0 * MINIMAP_BORDER;
+    let size = (inner * scale) as u32;
+    let offset = ((MINIMAP_MARGIN + MINIMAP_BORDER) * scale) as u32;
+    let position = UVec2::new(
+        window.physical_width().saturating_sub(offset + size),
+        window.physical_height().saturating_sub(offset + size),
+    );
+
+    let viewport = Viewport {
+        physical_position: position,
+        physical_size: UVec2::splat(size),
+        ..default()
+    };
+    if camera.viewport.as_ref().map(|v| (v.physical_position, v.physical_size))
+        != Some((viewport.physical_position, viewport.physical_size))
+    {
+        camera.viewport = Some(viewport);
+    }
+}
+
+fn draw_minimap(
+    ships: Query<(&Transform, &PathFollow, &RadarContact), With<ShipRoot>>,
+    stations: Query<&Transform, With<RadarStation>>,
+    forecast: Res<ConflictForecast>,
+    main_camera: Single<&PanOrbitState>,
+    mut gizmos: Gizmos<MinimapGizmos>,
+) {
+    let y = WATER_HEIGHT + 10.0;
+    let flat = |pos: Vec3| Vec3::new(pos.x, y, pos.z);
+    let up = Quat::from_rotation_x(std::f32::consts::FRAC_PI_2);
+
+    for (transform, path_follow, contact) in ships.iter() {
+        if contact.detected {
+            gizmos.linestrip(
+                path_follow.curve.iter_positions(50).map(|p| flat(p + path_follow.offset)),
+                Color::srgba(0.1, 0.8, 0.3, 0.5),
+            );
+            gizmos.circle(Isometry3d::new(flat(transform.translation), up), 3.0, Color::WHITE);
+        } else {
+            gizmos.circle(
+                Isometry3d::new(flat(transform.translation), up),
+                2.0,
+                Color::srgb(0.3, 1.0, 0.4),
+            );
+        }
+    }
+
+    for transform in stations.iter() {
+        gizmos.rect(
+            Isometry3d::new(flat(transform.translation), up),
+            Vec2::splat(5.0),
+            Color::srgb(1.0, 0.9, 0.6),
+        );
+    }
+
+    for conflict in forecast.conflicts.iter() {
+        let point = flat(conflict.point);
+        gizmos.line(point + Vec3::new(-4.0, 0.0, -4.0), point + Vec3::new(4.0, 0.0, 4.0), Color::srgb(1.0, 0.2, 0.1));
+        gizmos.line(point + Vec3::new(-4.0, 0.0, 4.0), point + Vec3::new(4.0, 0.0, -4.0), Color::srgb(1.0, 0.2, 0.1));
+    }
+
+    // where the main camera is looking
+    gizmos.circle(
+        Isometry3d::new(flat(main_camera.center), up),
+        8.0,
+        Color::srgba(1.0, 1.0, 1.0, 0.6),
+    );
+}
+
+fn minimap_click(
+    frame: Single<(&Interaction, &RelativeCursorPosition), With<MinimapFrame>>,
+    mut main_camera: Single<(&mut PanOrbitState, &mut CameraFollow)>,
+) {
+    let (interaction, cursor) = *frame;
+    if *interaction != Interaction::Pressed {
+        return;
+    }
+    let Some(normalized) = cursor.normalized else {
+        return;
+    };
+    // (0, 0) is the top left of the frame, which shows the -x/-z corner of the map
+    let half = MAP_SIZE as f32 / 2.0;
+    let point = (normalized.clamp(Vec2::ZERO, Vec2::ONE) - 0.5) * MAP_SIZE as f32;
+    let (state, follow) = &mut *main_camera;
+    state.center.x = point.x.clamp(-half, half);
+    state.center.z = point.y.clamp(-half, half);
+    follow.target = None;
+}
@@ -0,0 +1,108 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+use crate::config::{ConfigAssets, RonAssetPlugin};
+use crate::wreck::Wrecks;
+use crate::GameState;
+
+pub struct ModePlugin;
+
+impl Plugin for ModePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RonAssetPlugin::<GameModeList>::new(&["modes.ron"]))
+            .init_resource::<CurrentMode>()
+            .init_resource::<Lives>()
+            .add_systems(OnEnter(GameState::Game), start_mode);
+    }
+}
+
+/// All game modes, loaded from `assets/config/*.modes.ron`.
+#[derive(Asset, TypePath, Deserialize, Clone, Debug)]
+pub struct GameModeList {
+    pub modes: Vec<GameMode>,
+}
+
+/// The rules of a session.
+#[derive(Deserialize, Clone, Debug)]
+pub struct GameMode {
+    pub id: String,
+    pub name: String,
+    /// crashes the player can afford, the session ends when they are used up
+    #[serde(default = "default_lives")]
+    pub lives: u32,
+    /// sunk ships leave a wreck that others have to avoid
+    #[serde(default)]
+    pub wrecks: bool,
+    /// seconds a wreck stays
+    #[serde(default = "default_wreck_secs")]
+    pub wreck_secs: f32,
+}
+
+fn default_lives() -> u32 {
+    1
+}
+
+fn default_wreck_secs() -> f32 {
+    30.0
+}
+
+/// Id of the mode that the next session is played in, the first mode if empty.
+#[derive(Resource, Default, Debug, Clone)]
+pub struct CurrentMode(pub String);
+
+impl CurrentMode {
+    /// looks up the selected mode in the loaded mode list
+    pub fn get<'a>(
+        &self,
+        config: &ConfigAssets,
+        mode_lists: &'a Assets<GameModeList>,
+    ) -> Option<&'a GameMode> {
+        let modes = &mode_lists.get(&config.modes)?.modes;
+        modes
+            .iter()
+            .find(|mode| mode.id == self.0)
+            .or_else(|| modes.first())
+    }
+}
+
+/// Hulls left in the current session, every crash costs one.
+#[derive(Resource, Debug)]
+pub struct Lives {
+    pub remaining: u32,
+    pub total: u32,
+}
+
+impl Default for Lives {
+    fn default() -> Self {
+        Lives { remaining: 1, total: 1 }
+    }
+}
+
+impl Lives {
+    /// takes one hull, true if that was the last one
+    pub fn lose(&mut self) -> bool {
+        self.remaining = self.remaining.saturating_sub(1);
+        self.remaining == 0
+    }
+}
+
+fn start_mode(
+    mut commands: Commands,
+    config: Res<ConfigAssets>,
+    mode_lists: Res<Assets<GameModeList>>,
+    mut current_mode: ResMut<CurrentMode>,
+) {
+    let Some(mode) = current_mode.get(&config, &mode_lists) else {
+        warn!("no game modes loaded, playing with a single hull");
+        commands.insert_resource(Lives::default());
+        commands.insert_resource(Wrecks::default());
+        return;
+    };
+    // an empty or unknown id falls back to the first mode, remember which one that was
+    current_mode.0 = mode.id.clone();
+    let lives = mode.lives.max(1);
+    commands.insert_resource(Lives { remaining: lives, total: lives });
+    commands.insert_resource(Wrecks {
+        enabled: mode.wrecks,
+        secs: mode.wreck_secs,
+    });
+}
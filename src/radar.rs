@@ -0,0 +1,169 @@
+use std::f32::consts::FRAC_PI_2;
+use bevy::prelude::*;
+use serde::Deserialize;
+use crate::camera::TouchDraggable;
+use crate::config::ConfigAssets;
+use crate::daynight::TimeOfDay;
+use crate::level::{CurrentLevel, LevelList};
+use crate::map::WATER_HEIGHT;
+use crate::ship::ShipRoot;
+use crate::GameState;
+
+pub struct RadarPlugin;
+
+impl Plugin for RadarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Game), spawn_radar_stations)
+            .add_systems(
+                Update,
+                (detect_ships, draw_radar)
+                    .chain()
+                    .in_set(RadarSet)
+                    .run_if(in_state(GameState::Game)),
+            );
+    }
+}
+
+/// Systems that decide which ships are [`RadarContact::detected`].
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RadarSet;
+
+/// share of the radar range a lighthouse still covers at midnight
+const NIGHT_RANGE: f32 = 0.6;
+/// height of a radar station above the water
+const STATION_HEIGHT: f32 = 8.0;
+
+/// Where a radar station stands, part of a level's data.
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct RadarStationDef {
+    pub x: f32,
+    pub z: f32,
+    pub radius: f32,
+}
+
+/// stations used by levels that don't place their own
+pub fn default_radar_stations() -> Vec<RadarStationDef> {
+    vec![
+        RadarStationDef { x: -60.0, z: -60.0, radius: 70.0 },
+        RadarStationDef { x: 70.0, z: -20.0, radius: 60.0 },
+        RadarStationDef { x: -10.0, z: 75.0, radius: 60.0 },
+    ]
+}
+
+/// A lighthouse or radar station, ships within `radius` are fully visible.
+/// Stations can be dragged around to change what the player sees.
+#[derive(Component)]
+pub struct RadarStation {
+    pub radius: f32,
+}
+
+/// Whether any radar station sees this ship. Undetected ships are hidden
+/// and only show up as a blip.
+#[derive(Component, Default)]
+pub struct RadarContact {
+    pub detected: bool,
+}
+
+fn spawn_radar_stations(
+    mut commands: Commands,
+    config: Res<ConfigAssets>,
+    level_lists: Res<Assets<LevelList>>,
+    current_level: Res<CurrentLevel>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let stations = current_level
+        .get(&config, &level_lists)
+        .map(|level| level.radar_stations.clone())
+        .unwrap_or_else(default_radar_stations);
+
+    let mesh = meshes.add(Cylinder::new(1.5, STATION_HEIGHT));
+    let material = materials.add(Color::srgb(0.9, 0.9, 0.85));
+
+    for station in stations {
+        commands
+            .spawn((
+                RadarStation { radius: station.radius },
+                TouchDraggable,
+                StateScoped(GameState::Game),
+                Mesh3d(mesh.clone()),
+                MeshMaterial3d(material.clone()),
+                Transform::from_xyz(station.x, WATER_HEIGHT + STATION_HEIGHT / 2.0, station.z),
+            ))
+            .observe(on_drag_station)
+            .with_child((
+                PointLight {
+                    color: Color::srgb(1.0, 0.9, 0.6),
+                    shadows_enabled: false,
+                    ..default()
+                },
+                Transform::from_xyz(0.0, STATION_HEIGHT / 2.0 + 1.0, 0.0),
+            ));
+    }
+}
+
+fn on_drag_station(
+    drag: Trigger<Pointer<Drag>>,
+    mut stations: Query<&mut Transform, With<RadarStation>>,
+) {
+    if let Ok(mut transform) = stations.get_mut(drag.entity()) {
+        transform.translation.x += drag.delta.x * 0.15;
+        transform.translation.z += drag.delta.y * 0.15;
+    }
+}
+
+/// how much of its radius a station covers right now
+fn range_factor(time_of_day: &TimeOfDay) -> f32 {
+    NIGHT_RANGE.lerp(1.0, time_of_day.daylight())
+}
+
+fn detect_ships(
+    time_of_day: Res<TimeOfDay>,
+    stations: Query<(&Transform, &RadarStation)>,
+    mut ships: Query<
+        (&Transform, &mut RadarContact, &mut Visibility),
+        (With<ShipRoot>, Without<RadarStation>),
+    >,
+) {
+    let range = range_factor(&time_of_day);
+    for (transform, mut contact, mut visibility) in ships.iter_mut() {
+        let pos = transform.translation.xz();
+        let detected = stations.iter().any(|(station, radar)| {
+            station.translation.xz().distance(pos) <= radar.radius * range
+        });
+
+        contact.detected = detected;
+        let wanted = if detected { Visibility::Inherited } else { Visibility::Hidden };
+        visibility.set_if_neq(wanted);
+    }
+}
+
+fn draw_radar(
+    time: Res<Time>,
+    time_of_day: Res<TimeOfDay>,
+    stations: Query<(&Transform, &RadarStation)>,
+    ships: Query<(&Transform, &RadarContact), With<ShipRoot>>,
+    mut gizmos: Gizmos,
+) {
+    let flat = Quat::from_rotation_x(FRAC_PI_2);
+    let range = range_factor(&time_of_day);
+
+    for (transform, radar) in stations.iter() {
+        let center = Vec3::new(transform.translation.x, WATER_HEIGHT + 0.2, transform.translation.z);
+        gizmos.circle(
+            Isometry3d::new(center, flat),
+            radar.radius * range,
+            Color::srgba(0.3, 0.9, 0.4, 0.4),
+        );
+    }
+
+    // blips pulse so they read as radar returns rather than ships
+    let pulse = 1.0 + 0.5 * (time.elapsed_secs() * 4.0).sin();
+    for (transform, contact) in ships.iter() {
+        if contact.detected {
+            continue;
+        }
+        let center = Vec3::new(transform.translation.x, WATER_HEIGHT + 0.5, transform.translation.z);
+        gizmos.circle(Isometry3d::new(center, flat), 2.0 * pulse, Color::srgb(0.3, 1.0, 0.4));
+    }
+}
@@ -0,0 +1,173 @@
+use std::f32::consts::{PI, TAU};
+use bevy::math::curve::{EaseFunction, EasingCurve};
+use bevy::prelude::*;
+use serde::Deserialize;
+use crate::actions::ActionState;
+use crate::camera::{CameraFollow, CameraInputSet, CameraSet, PanOrbitState};
+use crate::config::ConfigAssets;
+use crate::level::{CurrentLevel, LevelList};
+use crate::GameState;
+
+pub struct RailPlugin;
+
+impl Plugin for RailPlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(Update, CameraInputSet.run_if(not(any_with_component::<CameraRail>)))
+            .add_systems(OnEnter(GameState::Game), start_intro_rail)
+            .add_systems(Update, play_camera_rail.before(CameraSet));
+    }
+}
+
+/// One keyframe of a camera rail, angles are in degrees.
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct RailKey {
+    pub center: Vec3,
+    pub radius: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+impl RailKey {
+    pub fn from_state(state: &PanOrbitState) -> Self {
+        RailKey {
+            center: state.center,
+            radius: state.radius,
+            yaw: state.yaw.to_degrees(),
+            pitch: state.pitch.to_degrees(),
+        }
+    }
+}
+
+/// A camera flight as it is written in a level, e.g. a fly-over of the harbor.
+#[derive(Deserialize, Clone, Debug)]
+pub struct CameraRailDef {
+    pub keys: Vec<RailKey>,
+    /// seconds from the first to the last key
+    pub duration: f32,
+    #[serde(default = "default_easing")]
+    pub easing: EaseFunction,
+}
+
+fn default_easing() -> EaseFunction {
+    EaseFunction::SineInOut
+}
+
+/// Moves the camera along keyframed curves, player camera input is ignored
+/// while a rail is playing. Removed once it reaches its last key, unless
+/// it holds there.
+#[derive(Component)]
+pub struct CameraRail {
+    center: CubicCurve<Vec3>,
+    radius: CubicCurve<f32>,
+    yaw: CubicCurve<f32>,
+    pitch: CubicCurve<f32>,
+    duration: f32,
+    elapsed: f32,
+    easing: EaseFunction,
+    /// any button jumps to the end
+    pub skippable: bool,
+    /// stay on the last key instead of handing the camera back
+    pub hold: bool,
+}
+
+impl CameraRail {
+    /// catmull-rom curves through `keys`, like the ship routes, `None` with fewer than two keys
+    pub fn new(keys: &[RailKey], duration: f32, easing: EaseFunction) -> Option<Self> {
+        // keep each yaw within half a turn of the previous one, so the camera turns the short way
+        let mut yaws: Vec<f32> = Vec::with_capacity(keys.len());
+        for key in keys {
+            let yaw = match yaws.last() {
+                Some(previous) => previous + (key.yaw - previous + 180.0).rem_euclid(360.0) - 180.0,
+                None => key.yaw,
+            };
+            yaws.push(yaw);
+        }
+
+        let centers: Vec<Vec3> = keys.iter().map(|k| k.center).collect();
+        let spline = |points: Vec<f32>| CubicCardinalSpline::new_catmull_rom(points).to_curve().ok();
+        Some(CameraRail {
+            center: CubicCardinalSpline::new_catmull_rom(centers).to_curve().ok()?,
+            radius: spline(keys.iter().map(|k| k.radius).collect())?,
+            yaw: spline(yaws.iter().map(|yaw| yaw.to_radians()).collect())?,
+            pitch: spline(keys.iter().map(|k| k.pitch.to_radians()).collect())?,
+            duration: duration.max(0.1),
+            elapsed: 0.0,
+            easing,
+            skippable: false,
+            hold: false,
+        })
+    }
+
+    /// flies from `from` down to `center` and circles it once
+    pub fn orbit(from: &PanOrbitState, center: Vec3, radius: f32, duration: f32) -> Self {
+        let start = RailKey::from_state(from);
+        let mut keys = vec![start];
+        keys.extend((1..=5).map(|i| RailKey {
+            center,
+            radius,
+            yaw: start.yaw + 90.0 * i as f32,
+            pitch: -30.0,
+        }));
+        Self::new(&keys, duration, EaseFunction::QuadraticOut).expect("orbit has enough keys")
+    }
+
+    fn finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    fn apply(&self, state: &mut PanOrbitState) {
+        let progress = (self.elapsed / self.duration).clamp(0.0, 1.0);
+        let eased = EasingCurve::new(0.0, 1.0, self.easing).sample_clamped(progress);
+        let t = eased * self.center.segments().len() as f32;
+        state.center = self.center.position(t);
+        state.radius = self.radius.position(t);
+        state.yaw = (self.yaw.position(t) + PI).rem_euclid(TAU) - PI;
+        state.pitch = self.pitch.position(t);
+        state.upside_down = false;
+    }
+}
+
+/// flies in over the level, ending where the player takes over
+fn start_intro_rail(
+    mut commands: Commands,
+    config: Res<ConfigAssets>,
+    level_lists: Res<Assets<LevelList>>,
+    current_level: Res<CurrentLevel>,
+    camera: Single<Entity, With<PanOrbitState>>,
+) {
+    commands.entity(*camera).remove::<CameraRail>();
+    let Some(intro) = current_level
+        .get(&config, &level_lists)
+        .and_then(|level| level.intro.as_ref())
+    else {
+        return;
+    };
+
+    let mut keys = intro.keys.clone();
+    keys.push(RailKey::from_state(&PanOrbitState::initial()));
+    if let Some(mut rail) = CameraRail::new(&keys, intro.duration, intro.easing) {
+        rail.skippable = true;
+        commands.entity(*camera).insert(rail);
+    }
+}
+
+fn play_camera_rail(
+    mut commands: Commands,
+    time: Res<Time>,
+    actions: Res<ActionState>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut camera: Single<(Entity, &mut CameraRail, &mut PanOrbitState, &mut CameraFollow)>,
+) {
+    let (entity, rail, state, follow) = &mut *camera;
+    follow.target = None;
+
+    rail.elapsed += time.delta_secs();
+    if rail.skippable && (actions.any_just_pressed() || mouse.get_just_pressed().next().is_some()) {
+        rail.elapsed = rail.duration;
+    }
+    rail.apply(state);
+
+    if rail.finished() && !rail.hold {
+        commands.entity(*entity).remove::<CameraRail>();
+    }
+}
@@ -0,0 +1,160 @@
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use crate::camera::PanOrbitState;
+use crate::collision::{CollisionEvent, ConflictForecast};
+use crate::weather::{Weather, WeatherKind};
+use crate::GameState;
+
+pub struct ShakePlugin;
+
+impl Plugin for ShakePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Game), calm_camera)
+            .add_systems(First, remove_camera_shake)
+            .add_systems(
+                Update,
+                (shake_on_collision, shake_on_near_miss, shake_in_storm)
+                    .run_if(in_state(GameState::Game)),
+            )
+            .add_systems(PostUpdate, apply_camera_shake.before(TransformSystem::TransformPropagate));
+    }
+}
+
+/// trauma a crash adds
+const COLLISION_TRAUMA: f32 = 0.8;
+/// trauma when two ships are about to pass close by
+const NEAR_MISS_TRAUMA: f32 = 0.3;
+/// a forecast conflict this close counts as a near miss
+const NEAR_MISS_SECS: f32 = 0.5;
+/// trauma a full storm never lets the camera drop below
+const STORM_TRAUMA: f32 = 0.25;
+
+/// Trauma based camera shake. Trauma is added by events and wears off over
+/// time, the shake grows with its square. The shake is an offset on the
+/// final camera transform and never touches [`PanOrbitState`].
+#[derive(Component)]
+pub struct CameraShake {
+    /// 0..1
+    pub trauma: f32,
+    /// trauma lost per second
+    pub decay: f32,
+    /// largest offset in world units, along the camera's right and up
+    pub max_offset: f32,
+    /// largest yaw, pitch and roll in radians
+    pub max_angle: f32,
+    /// how fast the shake wobbles
+    pub frequency: f32,
+    /// the shake can't go below this, e.g. while a storm is raging
+    pub floor: f32,
+    time: f32,
+    /// what was added last frame, taken off again before the camera moves
+    applied: (Vec3, Quat),
+}
+
+impl Default for CameraShake {
+    fn default() -> Self {
+        CameraShake {
+            trauma: 0.0,
+            decay: 0.8,
+            max_offset: 1.5,
+            max_angle: 3.0f32.to_radians(),
+            frequency: 15.0,
+            floor: 0.0,
+            time: 0.0,
+            applied: (Vec3::ZERO, Quat::IDENTITY),
+        }
+    }
+}
+
+impl CameraShake {
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.0);
+    }
+}
+
+/// smooth noise in -1..1, a few detuned sines are enough for a shake
+fn noise(seed: f32, t: f32) -> f32 {
+    ((t + seed).sin() + 0.5 * (2.3 * t + 1.7 * seed).sin() + 0.25 * (4.1 * t + 2.9 * seed).sin())
+        / 1.75
+}
+
+fn calm_camera(mut shake: Single<&mut CameraShake>) {
+    shake.trauma = 0.0;
+    shake.floor = 0.0;
+}
+
+/// takes last frame's shake off, so the camera systems see the real transform
+fn remove_camera_shake(mut q_camera: Query<(&mut CameraShake, &mut Transform)>) {
+    for (mut shake, mut transform) in &mut q_camera {
+        let (offset, rotation) = shake.applied;
+        if offset == Vec3::ZERO && rotation == Quat::IDENTITY {
+            continue;
+        }
+        transform.translation -= offset;
+        transform.rotation *= rotation.inverse();
+        shake.applied = (Vec3::ZERO, Quat::IDENTITY);
+    }
+}
+
+fn apply_camera_shake(
+    time: Res<Time>,
+    mut q_camera: Query<(&mut CameraShake, &mut Transform), With<PanOrbitState>>,
+) {
+    for (mut shake, mut transform) in &mut q_camera {
+        let delta = time.delta_secs();
+        shake.trauma = (shake.trauma - shake.decay * delta).max(shake.floor);
+        if shake.trauma <= 0.0 {
+            continue;
+        }
+        shake.time += delta * shake.frequency;
+
+        let amount = shake.trauma * shake.trauma;
+        let t = shake.time;
+        let angle = shake.max_angle * amount;
+        let rotation = Quat::from_euler(
+            EulerRot::YXZ,
+            angle * noise(1.0, t),
+            angle * noise(2.0, t),
+            angle * noise(3.0, t),
+        );
+        let offset = (transform.right() * noise(4.0, t) + transform.up() * noise(5.0, t))
+            * shake.max_offset
+            * amount;
+
+        transform.translation += offset;
+        transform.rotation *= rotation;
+        shake.applied = (offset, rotation);
+    }
+}
+
+fn shake_on_collision(
+    mut collisions: EventReader<CollisionEvent>,
+    mut shake: Single<&mut CameraShake>,
+) {
+    for _ in collisions.read() {
+        shake.add_trauma(COLLISION_TRAUMA);
+    }
+}
+
+/// shakes once for every pair of ships that is about to brush past each other
+fn shake_on_near_miss(
+    forecast: Res<ConflictForecast>,
+    mut shaken: Local<HashSet<(Entity, Entity)>>,
+    mut shake: Single<&mut CameraShake>,
+) {
+    let close: HashSet<(Entity, Entity)> = forecast
+        .conflicts
+        .iter()
+        .filter(|conflict| conflict.in_secs <= NEAR_MISS_SECS)
+        .map(|conflict| (conflict.entity_a, conflict.entity_b))
+        .collect();
+    let new_pairs = close.difference(&shaken).count();
+    if new_pairs > 0 {
+        shake.add_trauma(NEAR_MISS_TRAUMA * new_pairs as f32);
+    }
+    *shaken = close;
+}
+
+fn shake_in_storm(weather: Res<Weather>, mut shake: Single<&mut CameraShake>) {
+    shake.floor = STORM_TRAUMA * weather.share(WeatherKind::Storm);
+}
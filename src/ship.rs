@@ -1,12 +1,22 @@
-use crate::collision::{Collider, CollisionEvent};
+use crate::actions::{Action, ActionState};
+use crate::collision::{Collider, CollisionEvent, Invulnerable};
+use crate::difficulty::{Difficulty, DifficultySet};
+use crate::daynight::NavLight;
+use crate::dynamics::{MotionMode, ShipDynamics, ShipMotionSet};
+use crate::flow::FlowResponse;
+use crate::radar::RadarContact;
+use crate::weather::Weather;
 use crate::GameState;
 use bevy::prelude::*;
-use bevy::reflect::List;
 use bevy_asset_loader::prelude::*;
 use bevy_water::WaterParam;
 use rand::prelude::*;
 use std::time::Duration;
-use crate::camera::{PanCameraPlugin, PanOrbitCameraBundle, PanOrbitState};
+use crate::camera::{CameraFollow, PanCameraPlugin, PanOrbitCameraBundle, PanOrbitState, TouchDraggable};
+use crate::rail::CameraRail;
+use crate::wreck::Sinking;
+use crate::mode::Lives;
+use bevy::utils::HashSet;
 
 pub struct ShipPlugin;
 
@@ -33,16 +43,20 @@ pub struct ShipAssets {
 }
 
 impl ShipType {
-    fn sample(ship_assets: &ShipAssets) -> ShipType {
+    /// picks a random ship, `cargo_chance` is the probability of getting a cargo ship
+    fn sample(ship_assets: &ShipAssets, cargo_chance: f32) -> ShipType {
         let mut rng = rand::thread_rng();
-        let index: u8 = rng.gen_range(0..4);
-        match index {
-            0 => ShipType::SailShipA(ship_assets.sail_a.clone()),
-            1 => ShipType::SailShipB(ship_assets.sail_b.clone()),
-            2 => ShipType::ContainerShipA(ship_assets.cargo_a.clone()),
-            3 => ShipType::ContainerShipB(ship_assets.cargo_b.clone()),
-            4 => ShipType::ContainerShipC(ship_assets.cargo_c.clone()),
-            _ => unreachable!(),
+        if rng.gen_bool(cargo_chance as f64) {
+            match rng.gen_range(0..3) {
+                0 => ShipType::ContainerShipA(ship_assets.cargo_a.clone()),
+                1 => ShipType::ContainerShipB(ship_assets.cargo_b.clone()),
+                _ => ShipType::ContainerShipC(ship_assets.cargo_c.clone()),
+            }
+        } else {
+            match rng.gen_range(0..2) {
+                0 => ShipType::SailShipA(ship_assets.sail_a.clone()),
+                _ => ShipType::SailShipB(ship_assets.sail_b.clone()),
+            }
         }
     }
 }
@@ -62,6 +76,19 @@ impl ShipAssets {
     }
 }
 
+/// Hull length at which a ship follows the wave slope completely,
+/// longer hulls tilt proportionally less.
+const REFERENCE_HULL_LENGTH: f32 = 6.0;
+/// how much higher the water at the bow has to be than at the stern for a splash
+const BOW_SPLASH_RISE: f32 = 0.4;
+
+/// A ship's bow cut into a wave at `position`.
+#[derive(Event)]
+pub struct BowSplash {
+    pub ship: Entity,
+    pub position: Vec3,
+}
+
 #[derive(Component)]
 #[require(Transform)]
 pub(crate) struct Ship {
@@ -69,39 +96,81 @@ pub(crate) struct Ship {
     front: Vec3,
     back_left: Vec3,
     back_right: Vec3,
+    /// 0 keeps the hull level, 1 aligns it fully with the wave normal
+    tilt: f32,
+    /// how quickly pitch, roll and heave settle on the waves
+    damping: f32,
 }
 
+/// Marks the top-level entity of a ship, which owns the route, the collider
+/// and the [`Ship`] model as a child.
 #[derive(Component)]
-struct PathFollow {
+pub(crate) struct ShipRoot;
+
+/// The ship the player clicked last, the camera follows it.
+#[derive(Resource, Default)]
+pub(crate) struct SelectedShip(pub Option<Entity>);
+
+/// Number of [`ShipRoot`]s currently alive, kept up to date by observers.
+#[derive(Resource, Default)]
+pub(crate) struct LiveShips(pub u32);
+
+#[derive(Component)]
+pub(crate) struct PathFollow {
     pub curve: CubicCurve<Vec3>,
-    t: f32,
+    pub t: f32,
+    /// how far wind and currents have pushed the ship off its route
+    pub offset: Vec3,
 }
 
 
 impl Ship {
     pub fn new(water_line: f32, front: f32, back: f32, left: f32, right: f32) -> Self {
+        let length = (back - front).abs().max(f32::EPSILON);
         Self {
             water_line,
             front: Vec3::new(0.0, 0.0, front),
             back_left: Vec3::new(left, 0.0, back),
             back_right: Vec3::new(right, 0.0, back),
+            tilt: (REFERENCE_HULL_LENGTH / length).min(1.0),
+            damping: 6.0,
         }
     }
 
-    fn update(&self, water: &WaterParam, pos: Vec3, transform: &mut Transform) {
-        let (yaw, _pitch, _roll) = transform.rotation.to_euler(EulerRot::YXZ);
+    /// Floats the ship on the waves. `heading` is the rotation of the parent
+    /// [`PathFollow`] entity, which keeps steering while this only adds pitch and roll.
+    /// `flooding` lowers the front, back left and back right probes, a sinking
+    /// hull settles lower and tilts towards the side that takes on the most water.
+    /// Returns the bow when it runs into a wave crest.
+    fn update(
+        &self,
+        water: &WaterParam,
+        pos: Vec3,
+        heading: Quat,
+        flooding: Vec3,
+        delta: f32,
+        transform: &mut Transform,
+    ) -> Option<Vec3> {
+        let (yaw, _pitch, _roll) = heading.to_euler(EulerRot::YXZ);
         let global = Transform::from_translation(pos).with_rotation(Quat::from_rotation_y(yaw));
 
         // Get the wave position at the front, back_left and back_right.
-        let mut front = water.wave_point(global.transform_point(self.front));
-        let left = water.wave_point(global.transform_point(self.back_left));
-        let right = water.wave_point(global.transform_point(self.back_right));
-        let normal = (left - front).cross(right - front).normalize();
-
-        front.y += self.water_line - 0.1;
-        // transform.look_at(front, normal); // fucks with the outer transformation
-
-        transform.translation.y = ((front.y + left.y + right.y) / 3.0) + self.water_line;
+        let front = water.wave_point(global.transform_point(self.front)) - Vec3::Y * flooding.x;
+        let left = water.wave_point(global.transform_point(self.back_left)) - Vec3::Y * flooding.y;
+        let right = water.wave_point(global.transform_point(self.back_right)) - Vec3::Y * flooding.z;
+        let normal = (left - front).cross(right - front).normalize_or(Vec3::Y);
+
+        // bring the normal into the parent's space, which is mirrored along z
+        let mut local_normal = heading.inverse() * normal;
+        local_normal.z = -local_normal.z;
+        let tilt = Quat::IDENTITY.slerp(Quat::from_rotation_arc(Vec3::Y, local_normal), self.tilt);
+        transform.rotation.smooth_nudge(&tilt, self.damping, delta);
+
+        let height = ((front.y + left.y + right.y) / 3.0) + self.water_line;
+        transform.translation.y.smooth_nudge(&height, self.damping, delta);
+
+        // the bow climbing a wave while the stern is still in the trough throws spray
+        (front.y - (left.y + right.y) / 2.0 > BOW_SPLASH_RISE).then_some(front)
     }
 }
 
@@ -114,13 +183,64 @@ fn point_on_map() -> f32 {
     rand_num as f32
 }
 
-impl Command for Ship {
+/// random closed route over the map
+/// height of the route control points, just under the water line
+const ROUTE_HEIGHT: f32 = -0.2;
+
+fn random_route() -> Vec<Vec3> {
+    let mut control_points: Vec<Vec3> = Vec::new();
+    for _ in 0..5 {
+        control_points.push(Vec3::new(point_on_map(), ROUTE_HEIGHT, point_on_map()))
+    }
+    control_points
+}
+
+/// Rolls routes until one starts far enough away from every collider and from
+/// where the other ships are heading, gives up after `max_attempts`.
+fn find_safe_route(world: &mut World, safety: &SpawnSafety) -> Option<Vec<Vec3>> {
+    let obstacles: Vec<Rect> = world
+        .query::<(&Transform, &Collider)>()
+        .iter(world)
+        .map(|(transform, collider)| collider.world_rect(transform.translation).inflate(safety.clearance))
+        .collect();
+
+    let mut predicted: Vec<Vec2> = Vec::new();
+    for path_follow in world.query::<&PathFollow>().iter(world) {
+        for step in 0..=PREDICTION_STEPS {
+            let t = path_follow.t + safety.prediction_horizon * step as f32 / PREDICTION_STEPS as f32;
+            predicted.push(path_follow.curve.position(t).xz());
+        }
+    }
+
+    for _ in 0..safety.max_attempts {
+        let control_points = random_route();
+        let start = control_points[0].xz();
+        let blocked = obstacles.iter().any(|rect| rect.contains(start))
+            || predicted
+                .iter()
+                .any(|point| point.distance(start) < safety.clearance);
+        if !blocked {
+            return Some(control_points);
+        }
+    }
+    None
+}
+
+/// Spawns a random ship on a safe route, the hull follows from the ship type.
+pub(crate) struct SpawnShip;
+
+impl Command for SpawnShip {
     fn apply(self, world: &mut World) {
+        if !world.contains_resource::<ShipAssets>() {
+            return;
+        }
+        let safety = world.get_resource::<SpawnSafety>().cloned().unwrap_or_default();
+        let Some(control_points) = find_safe_route(world, &safety) else {
+            warn!("no safe spawn point found after {} attempts", safety.max_attempts);
+            return;
+        };
+
         if let Some(ship_assets) = world.get_resource::<ShipAssets>() {
-            let mut control_points: Vec<Vec3> = Vec::new();
-            for _ in 0..5 {
-                control_points.push(Vec3::new(point_on_map(), -0.2, point_on_map()))
-            }
             let curve = CubicCardinalSpline::new_catmull_rom(control_points.clone())
                 .to_curve_cyclic()
                 .unwrap();
@@ -128,57 +248,107 @@ impl Command for Ship {
             let first_pos: Vec3 = control_points[0];
             let mut transform = Transform::from_translation(first_pos).with_scale(Vec3::new(2., 2., -2.));
 
-            let ship_type = ShipType::sample(ship_assets);
-            let (h, r) = match ship_type {
-                ShipType::SailShipA(h) | ShipType::SailShipB(h) => (h, Rect::new(0., 0., 4., 6.)),
+            let cargo_chance = world
+                .get_resource::<Difficulty>()
+                .map_or(0.5, |difficulty| difficulty.cargo_chance);
+            let ship_type = ShipType::sample(ship_assets, cargo_chance);
+            let (h, r, hull, dynamics, flow, name) = match ship_type {
+                ShipType::SailShipA(h) | ShipType::SailShipB(h) => (
+                    h,
+                    Rect::new(0., 0., 4., 6.),
+                    Ship::new(-0.100, -3., 3.0, -2.0, 2.0),
+                    ShipDynamics::sail(),
+                    FlowResponse::sail(),
+                    Name::new("Sailer"),
+                ),
                 ShipType::ContainerShipA(h)
                 | ShipType::ContainerShipB(h)
-                | ShipType::ContainerShipC(h) => (h, Rect::new(0., 0., 8., 12.)),
+                | ShipType::ContainerShipC(h) => (
+                    h,
+                    Rect::new(0., 0., 8., 12.),
+                    Ship::new(-0.100, -6., 6.0, -4.0, 4.0),
+                    ShipDynamics::cargo(),
+                    FlowResponse::cargo(),
+                    Name::new("Freighter"),
+                ),
             };
 
+            let beam = hull.back_right.x / 2.0;
             world
                 .spawn((
+                    ShipRoot,
+                    name,
+                    StateScoped(GameState::Game),
                     transform,
-                    PathFollow { curve, t: 0.0 },
+                    Visibility::default(),
+                    RadarContact::default(),
+                    TouchDraggable,
+                    PathFollow { curve, t: 0.0, offset: Vec3::ZERO },
+                    dynamics,
+                    flow,
                     Collider { rect: r },
+                    Invulnerable(Timer::from_seconds(safety.grace_secs, TimerMode::Once)),
                 ))
 
+                .observe(start_route_sketch)
                 .observe(on_drag_follow)
+                .observe(finish_route_sketch)
+                .observe(on_click_select)
 
                 .with_children(|parent| {
                     parent
                         .spawn((
                             SceneRoot(h.into()),
-                            Ship::new(-0.100, -3., 3.0, -2.0, 2.0),
+                            hull,
                             Transform::default(),
                         ))
                         .with_children(|parent| {
-                            parent.spawn((
-                                PointLight {
-                                    shadows_enabled: false,
-                                    ..default()
-                                },
-                                Transform::from_xyz(0., 5., 0.),
-                            ));
+                            // the hull is scaled by 2, light positions are in model space
+                            parent.spawn((NavLight::port(), Transform::from_xyz(-beam, 1., 0.)));
+                            parent.spawn((NavLight::starboard(), Transform::from_xyz(beam, 1., 0.)));
+                            parent.spawn((NavLight::masthead(), Transform::from_xyz(0., 5., 0.)));
                         });
                 });
         }
     }
 }
 
+/// samples per predicted route when checking spawn points
+const PREDICTION_STEPS: u32 = 10;
+
+/// Rules for where new ships may appear.
+#[derive(Resource, Clone, Debug)]
+pub struct SpawnSafety {
+    /// minimum distance between a spawn point and colliders or predicted routes
+    pub clearance: f32,
+    /// how far ahead other ships' routes are predicted, in route progress
+    pub prediction_horizon: f32,
+    /// how many random routes are tried before the spawn is skipped
+    pub max_attempts: u32,
+    /// seconds a new ship can't collide with anything
+    pub grace_secs: f32,
+}
+
+impl Default for SpawnSafety {
+    fn default() -> Self {
+        Self {
+            clearance: 12.0,
+            prediction_horizon: 0.5,
+            max_attempts: 10,
+            grace_secs: 2.0,
+        }
+    }
+}
+
 #[derive(Resource)]
 struct ShipSpawnManager {
     spawn_timer: Timer,
-    current_ships: u32,
-    max_ships: u32,
 }
 
 impl Default for ShipSpawnManager {
     fn default() -> Self {
         Self {
             spawn_timer: Timer::new(Duration::from_secs(10), TimerMode::Repeating),
-            current_ships: 1,
-            max_ships: 10,
         }
     }
 }
@@ -187,21 +357,30 @@ fn spawn_ships(
     mut commands: Commands,
     time: Res<Time>,
     mut spawn_manager: ResMut<ShipSpawnManager>,
+    difficulty: Res<Difficulty>,
+    live_ships: Res<LiveShips>,
 ) {
+    let interval = Duration::from_secs_f32(difficulty.spawn_interval);
+    if spawn_manager.spawn_timer.duration() != interval {
+        spawn_manager.spawn_timer.set_duration(interval);
+    }
     spawn_manager.spawn_timer.tick(time.delta());
 
     // if it finished, despawn the bomb
-    if spawn_manager.spawn_timer.finished() && spawn_manager.current_ships < spawn_manager.max_ships
+    if spawn_manager.spawn_timer.finished() && live_ships.0 < difficulty.max_ships
     {
-        commands.queue(Ship::new(-0.100, -3., 3.0, -2.0, 2.0));
-        spawn_manager.current_ships += 1;
+        commands.queue(SpawnShip);
     }
 }
 
 /// This system uses gizmos to draw the current [`Curve`] by breaking it up into a large number
 /// of line segments.
-fn draw_follow_path(path_follow_query: Query<&PathFollow>, mut gizmos: Gizmos) {
-    for (p) in &path_follow_query {
+fn draw_follow_path(path_follow_query: Query<(&PathFollow, &RadarContact)>, mut gizmos: Gizmos) {
+    for (p, contact) in &path_follow_query {
+        // routes of ships outside radar range are unknown
+        if !contact.detected {
+            continue;
+        }
         // Scale resolution with curve length so it doesn't degrade as the length increases.
         //  let resolution = 100 * p.curve. .len();
         gizmos.linestrip(
@@ -213,77 +392,410 @@ fn draw_follow_path(path_follow_query: Query<&PathFollow>, mut gizmos: Gizmos) {
 
 pub fn move_ship(
     mut query: Query<(&mut PathFollow, &mut Transform)>,
-    time: Res<Time>) {
+    time: Res<Time>,
+    difficulty: Res<Difficulty>,
+    weather: Res<Weather>) {
+    let speed = difficulty.ship_speed * weather.profile.ship_speed;
     for (mut path_follow, mut ship_transform) in query.iter_mut() {
-        path_follow.t += 0.1 * time.delta_secs();
+        path_follow.t += speed * time.delta_secs();
 
         if path_follow.t > path_follow.curve.segments().len() as f32 {
             path_follow.t = 0.0;
         }
 
-        let pos = path_follow.curve.position(path_follow.t);
+        let pos = path_follow.curve.position(path_follow.t) + path_follow.offset;
 
         ship_transform
             .translation
             .smooth_nudge(&pos, 5.0, time.delta_secs());
 
-        ship_transform.look_at(path_follow.curve.position(path_follow.t + 0.01), Dir3::Y)
+        ship_transform.look_at(path_follow.curve.position(path_follow.t + 0.01) + path_follow.offset, Dir3::Y)
+    }
+}
+
+/// impulse per pixel of drag in [`MotionMode::Physics`]
+const DRAG_IMPULSE: f32 = 0.6;
+
+/// world units between two points of a route drawn with a finger
+const SKETCH_SPACING: f32 = 6.0;
+/// drawn points needed before a sketch replaces the route
+const SKETCH_MIN_POINTS: usize = 3;
+
+/// A new route a finger is drawing from the ship, it replaces the
+/// ship's route when the finger is lifted.
+#[derive(Component, Default)]
+struct RouteSketch(Vec<Vec3>);
+
+/// where a pointer at `position` on the screen touches the water
+fn pointer_on_water(
+    camera: &Query<(&Camera, &GlobalTransform), With<PanOrbitState>>,
+    position: Vec2,
+) -> Option<Vec3> {
+    let (camera, camera_transform) = camera.get_single().ok()?;
+    let ray = camera.viewport_to_world(camera_transform, position).ok()?;
+    let distance = ray.intersect_plane(Vec3::new(0.0, ROUTE_HEIGHT, 0.0), InfinitePlane3d::new(Vec3::Y))?;
+    Some(ray.get_point(distance))
+}
+
+/// fingers draw a new route, the mouse pushes ships around
+fn start_route_sketch(drag: Trigger<Pointer<DragStart>>, mut commands: Commands) {
+    if drag.pointer_id.is_touch() {
+        commands.entity(drag.entity()).insert(RouteSketch::default());
     }
 }
 
 fn on_drag_follow(
     drag: Trigger<Pointer<Drag>>,
-    mut transforms: Query<&mut Transform, With<PathFollow>>,
+    mode: Res<MotionMode>,
+    camera: Query<(&Camera, &GlobalTransform), With<PanOrbitState>>,
+    mut transforms: Query<(&mut Transform, &mut ShipDynamics, Option<&mut RouteSketch>), With<PathFollow>>,
+) {
+    let Ok((mut transform, mut dynamics, sketch)) = transforms.get_mut(drag.entity()) else {
+        return;
+    };
+    match sketch {
+        Some(mut sketch) => {
+            let Some(point) = pointer_on_water(&camera, drag.pointer_location.position) else {
+                return;
+            };
+            let last = sketch.0.last().copied().unwrap_or(transform.translation.with_y(ROUTE_HEIGHT));
+            if point.distance(last) >= SKETCH_SPACING {
+                sketch.0.push(point);
+            }
+        }
+        None => push_ship(*mode, &mut transform, &mut dynamics, drag.delta),
+    }
+}
+
+/// turns a finished sketch into the ship's route, starting where the ship is
+fn finish_route_sketch(
+    drag: Trigger<Pointer<DragEnd>>,
+    mut commands: Commands,
+    mut ships: Query<(&Transform, &mut PathFollow, &RouteSketch)>,
 ) {
-    if let Ok(mut transform) = transforms.get_mut(drag.entity()) {
-        transform.translation.x += drag.delta.x * 0.15;
-        transform.translation.z += drag.delta.y * 0.15;
+    let Ok((transform, mut path_follow, sketch)) = ships.get_mut(drag.entity()) else {
+        return;
+    };
+    if let Some(curve) = sketched_route(transform.translation, &sketch.0) {
+        *path_follow = PathFollow { curve, t: 0.0, offset: Vec3::ZERO };
     }
+    commands.entity(drag.entity()).remove::<RouteSketch>();
+}
+
+/// a looping route from `start` through the drawn points and back,
+/// `None` when too little was drawn
+fn sketched_route(start: Vec3, sketch: &[Vec3]) -> Option<CubicCurve<Vec3>> {
+    if sketch.len() < SKETCH_MIN_POINTS {
+        return None;
+    }
+    let control_points: Vec<Vec3> = std::iter::once(start.with_y(ROUTE_HEIGHT))
+        .chain(sketch.iter().copied())
+        .collect();
+    CubicCardinalSpline::new_catmull_rom(control_points).to_curve_cyclic().ok()
+}
+
+fn draw_route_sketches(sketches: Query<(&Transform, &RouteSketch)>, mut gizmos: Gizmos) {
+    for (transform, sketch) in &sketches {
+        let start = transform.translation.with_y(ROUTE_HEIGHT);
+        gizmos.linestrip(
+            std::iter::once(start).chain(sketch.0.iter().copied()),
+            Color::srgba(0.9, 0.8, 0.2, 0.8),
+        );
+    }
+}
+
+/// moves a ship by `delta` screen pixels, or pushes it that way with physics
+fn push_ship(mode: MotionMode, transform: &mut Transform, dynamics: &mut ShipDynamics, delta: Vec2) {
+    match mode {
+        MotionMode::Kinematic => {
+            transform.translation.x += delta.x * 0.15;
+            transform.translation.z += delta.y * 0.15;
+        }
+        MotionMode::Physics => {
+            dynamics.apply_impulse(Vec3::new(delta.x, 0.0, delta.y) * DRAG_IMPULSE);
+        }
+    }
+}
+
+/// how many pixels per second of dragging the ship buttons and stick are worth
+const STEER_SPEED: f32 = 120.0;
+
+fn steer_selected_ship(
+    time: Res<Time>,
+    actions: Res<ActionState>,
+    mode: Res<MotionMode>,
+    selected: Res<SelectedShip>,
+    mut ships: Query<(&mut Transform, &mut ShipDynamics), With<PathFollow>>,
+) {
+    let axis = actions.steer_axis();
+    if axis == Vec2::ZERO {
+        return;
+    }
+    let Some(Ok((mut transform, mut dynamics))) = selected.0.map(|entity| ships.get_mut(entity)) else {
+        return;
+    };
+    // up on the stick is up on the screen, which is -z like dragging
+    let delta = Vec2::new(axis.x, -axis.y) * STEER_SPEED * time.delta_secs();
+    push_ship(*mode, &mut transform, &mut dynamics, delta);
+}
+
+/// cycles the selection through the ships, the camera follows along
+fn select_next_ship(
+    actions: Res<ActionState>,
+    mut selected: ResMut<SelectedShip>,
+    ships: Query<Entity, With<ShipRoot>>,
+    mut camera: Single<&mut CameraFollow>,
+) {
+    if !actions.just_pressed(Action::NextShip) {
+        return;
+    }
+    let mut entities: Vec<Entity> = ships.iter().collect();
+    entities.sort();
+    let next = match selected.0.and_then(|current| entities.iter().position(|e| *e == current)) {
+        Some(index) => entities.get(index + 1).or(entities.first()),
+        None => entities.first(),
+    };
+    if let Some(&entity) = next {
+        selected.0 = Some(entity);
+        **camera = CameraFollow::track(entity);
+    }
+}
+
+fn on_click_select(
+    click: Trigger<Pointer<Click>>,
+    mut selected: ResMut<SelectedShip>,
+    mut camera: Single<&mut CameraFollow>,
+) {
+    selected.0 = Some(click.entity());
+    **camera = CameraFollow::track(click.entity());
+}
+
+fn draw_selection(
+    selected: Res<SelectedShip>,
+    ships: Query<&Transform, With<ShipRoot>>,
+    mut gizmos: Gizmos,
+) {
+    let Some(transform) = selected.0.and_then(|entity| ships.get(entity).ok()) else {
+        return;
+    };
+    gizmos.circle(
+        Isometry3d::new(transform.translation, Quat::from_rotation_x(std::f32::consts::FRAC_PI_2)),
+        10.0,
+        Color::srgb(1.0, 0.85, 0.2),
+    );
 }
 
 pub fn update_ships(
     water: WaterParam,
-    mut ships: Query<(&Ship, &mut Transform, &GlobalTransform)>,
+    time: Res<Time>,
+    mut ships: Query<(&Ship, &mut Transform, &GlobalTransform, &Parent)>,
+    headings: Query<(&Transform, Option<&Sinking>), (With<ShipRoot>, Without<Ship>)>,
+    mut splashes: EventWriter<BowSplash>,
 ) {
-    for (ship, mut transform, global) in ships.iter_mut() {
+    for (ship, mut transform, global, parent) in ships.iter_mut() {
+        let Ok((heading, sinking)) = headings.get(parent.get()) else {
+            continue;
+        };
         let pos = global.translation();
-        ship.update(&water, pos, &mut transform);
+        let flooding = sinking.map_or(Vec3::ZERO, Sinking::flooding);
+        if let Some(bow) = ship.update(&water, pos, heading.rotation, flooding, time.delta_secs(), &mut transform) {
+            splashes.send(BowSplash { ship: parent.get(), position: bow });
+        }
     }
 }
 
 impl Plugin for ShipPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Game), setup)
-            .add_systems(OnEnter(GameState::GameOver), cleanup_ships)
-            .add_systems(Update, spawn_ships.run_if(in_state(GameState::Game)))
-            .add_systems(Update, update_ships.run_if(in_state(GameState::Game)))
-            .add_systems(Update, draw_follow_path.run_if(in_state(GameState::Game)))
-            .add_systems(Update, ship_collide_event.run_if(in_state(GameState::Game)))
-            .add_systems(Update, move_ship.run_if(in_state(GameState::Game)));
+        app.add_event::<BowSplash>()
+            .init_resource::<LiveShips>()
+            .init_resource::<SelectedShip>()
+            .init_resource::<SpawnSafety>()
+            .add_observer(count_spawned_ship)
+            .add_observer(count_despawned_ship)
+            .add_systems(OnEnter(GameState::Game), setup)
+            .add_systems(Update, spawn_ships.after(DifficultySet).run_if(in_state(GameState::Game)))
+            .add_systems(Update, update_ships.after(ShipMotionSet).run_if(in_state(GameState::Game)))
+            .add_systems(
+                Update,
+                (draw_follow_path, draw_route_sketches, draw_selection).run_if(in_state(GameState::Game)),
+            )
+            .add_systems(Update, (select_next_ship, steer_selected_ship).run_if(in_state(GameState::Game)))
+            .add_systems(
+                Update,
+                (ship_collide_event, tick_crash_replay.run_if(resource_exists::<CrashReplay>))
+                    .chain()
+                    .run_if(in_state(GameState::Game)),
+            )
+            .add_systems(
+                Update,
+                move_ship
+                    .in_set(ShipMotionSet)
+                    .after(DifficultySet)
+                    .run_if(in_state(GameState::Game))
+                    .run_if(resource_equals(MotionMode::Kinematic)),
+            );
     }
 }
 
 fn setup(mut commands: Commands) {
-    commands.queue(Ship::new(-0.100, -3., 3.0, -2.0, 2.0));
-    commands.init_resource::<ShipSpawnManager>();
+    commands.queue(SpawnShip);
+    commands.insert_resource(ShipSpawnManager::default());
+    commands.insert_resource(SelectedShip::default());
 }
 
-fn cleanup_ships(mut commands: Commands, query: Query<Entity, With<Ship>>) {
-    for entity in query.iter() {
-        commands.entity(entity).despawn_recursive();
-    }
-    commands.remove_resource::<ShipSpawnManager>();
+fn count_spawned_ship(_trigger: Trigger<OnAdd, ShipRoot>, mut live_ships: ResMut<LiveShips>) {
+    live_ships.0 += 1;
+}
+
+fn count_despawned_ship(_trigger: Trigger<OnRemove, ShipRoot>, mut live_ships: ResMut<LiveShips>) {
+    live_ships.0 = live_ships.0.saturating_sub(1);
 }
 
+/// seconds the camera circles a crash before the game over screen
+const CRASH_REPLAY_SECS: f32 = 5.0;
+
+/// Running while the camera circles the crash, the session ends when it finishes.
+#[derive(Resource)]
+pub struct CrashReplay(Timer);
+
+/// Every crash costs a hull, both ships sink. Losing the last hull plays
+/// the crash replay, which ends the session.
 fn ship_collide_event(mut ship_collision: EventReader<CollisionEvent>,
                       mut commands: Commands,
-                      transform_query: Query<&Transform, With<PathFollow>>,
-                      mut camera: Single<&mut PanOrbitState>
+                      replay: Option<Res<CrashReplay>>,
+                      mut lives: ResMut<Lives>,
+                      transform_query: Query<&Transform, With<ShipRoot>>,
+                      camera: Single<(Entity, &PanOrbitState)>,
+                      mut crashed: Local<HashSet<Entity>>,
 ) {
+    // a crash can be reported from both sides and for a few frames, count it once
+    crashed.retain(|entity| transform_query.contains(*entity));
     for ev in ship_collision.read() {
-        println!("Entity {:?} collided with {:?}", ev.entity_a, ev.entity_b);
-        let ship_transform = transform_query.get(ev.entity_a).unwrap();
-        camera.center = ship_transform.translation;
-        camera.radius += 5000.;
+        let ships: Vec<Entity> = [ev.entity_a, ev.entity_b]
+            .into_iter()
+            .filter(|entity| transform_query.contains(*entity))
+            .collect();
+        if ships.is_empty() || ships.iter().any(|ship| crashed.contains(ship)) {
+            continue;
+        }
+        crashed.extend(ships.iter().copied());
+        debug!("{:?} collided with {:?}", ev.entity_a, ev.entity_b);
+        if replay.is_some() || !lives.lose() {
+            continue;
+        }
+        let Ok(ship) = transform_query.get(ships[0]) else {
+            continue;
+        };
+        let (entity, state) = *camera;
+        let mut rail = CameraRail::orbit(state, ship.translation, 30.0, CRASH_REPLAY_SECS);
+        rail.hold = true;
+        commands.entity(entity).insert(rail);
+        commands.insert_resource(CrashReplay(Timer::from_seconds(CRASH_REPLAY_SECS, TimerMode::Once)));
+        return;
+    }
+}
+
+fn tick_crash_replay(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut replay: ResMut<CrashReplay>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if replay.0.tick(time.delta()).just_finished() {
+        commands.remove_resource::<CrashReplay>();
+        next_state.set(GameState::GameOver);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::gizmos::GizmoPlugin;
+    use bevy::state::app::StatesPlugin;
+    use bevy_water::WaterSettings;
+    use crate::mode::Lives;
+    use super::*;
+
+    fn ship_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, AssetPlugin::default()))
+            // the gizmo plugin registers its shaders even without a renderer
+            .init_asset::<Shader>()
+            .add_plugins((GizmoPlugin, ShipPlugin))
+            .init_state::<GameState>()
+            .enable_state_scoped_entities::<GameState>()
+            .add_event::<CollisionEvent>()
+            // ships follow their routes, which move_ship slows down with the weather
+            .init_resource::<MotionMode>()
+            .init_resource::<Weather>()
+            // no spawn is ever rejected, so every queued ship appears
+            .insert_resource(SpawnSafety {
+                clearance: 0.0,
+                max_attempts: 1000,
+                ..default()
+            })
+            .init_resource::<Difficulty>()
+            .init_resource::<Lives>()
+            .init_resource::<ActionState>()
+            .init_resource::<WaterSettings>()
+            .insert_resource(ShipAssets {
+                sail_a: Handle::default(),
+                sail_b: Handle::default(),
+                cargo_a: Handle::default(),
+                cargo_b: Handle::default(),
+                cargo_c: Handle::default(),
+            });
+        app.world_mut().spawn((PanOrbitState::initial(), CameraFollow::default()));
+        app
+    }
+
+    fn enter(app: &mut App, state: GameState) {
+        app.world_mut().resource_mut::<NextState<GameState>>().set(state);
+        app.update();
+    }
+
+    fn count<F: bevy::ecs::query::QueryFilter>(app: &mut App) -> usize {
+        app.world_mut().query_filtered::<(), F>().iter(app.world()).count()
+    }
+
+    #[test]
+    fn a_drawn_route_starts_at_the_ship_and_passes_the_finger() {
+        let start = Vec3::new(10.0, 3.0, 10.0);
+        let sketch = [
+            Vec3::new(20.0, ROUTE_HEIGHT, 10.0),
+            Vec3::new(30.0, ROUTE_HEIGHT, 20.0),
+            Vec3::new(20.0, ROUTE_HEIGHT, 30.0),
+        ];
+        assert!(sketched_route(start, &sketch[..2]).is_none());
+
+        let curve = sketched_route(start, &sketch).unwrap();
+        assert_eq!(curve.segments().len(), 4);
+        assert!(curve.position(0.0).distance(start.with_y(ROUTE_HEIGHT)) < 1e-4);
+        for (i, point) in sketch.iter().enumerate() {
+            assert!(curve.position(i as f32 + 1.0).distance(*point) < 1e-4);
+        }
+    }
+
+    #[test]
+    fn ships_are_cleaned_up_between_sessions() {
+        let mut app = ship_app();
+        for _ in 0..2 {
+            enter(&mut app, GameState::Game);
+            app.world_mut().commands().queue(SpawnShip);
+            app.world_mut().commands().queue(SpawnShip);
+            app.update();
+
+            // the first ship from entering the game and the two queued ones
+            assert_eq!(count::<With<ShipRoot>>(&mut app), 3);
+            assert_eq!(app.world().resource::<LiveShips>().0, 3);
+
+            enter(&mut app, GameState::GameOver);
+            assert_eq!(app.world().resource::<LiveShips>().0, 0);
+            assert_eq!(count::<With<ShipRoot>>(&mut app), 0);
+            assert_eq!(count::<With<Collider>>(&mut app), 0);
+            assert_eq!(count::<With<PathFollow>>(&mut app), 0);
+            assert_eq!(count::<With<Ship>>(&mut app), 0);
+            assert_eq!(count::<With<NavLight>>(&mut app), 0);
+            assert_eq!(count::<With<Parent>>(&mut app), 0);
+        }
     }
 }
@@ -0,0 +1,248 @@
+use bevy::audio::{DefaultSpatialScale, SpatialScale, Volume};
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use crate::camera::PanOrbitState;
+use crate::collision::{CollisionEvent, ConflictForecast};
+use crate::ship::{PathFollow, ShipRoot};
+use crate::storage::{SaveData, Settings};
+use crate::tension::Tension;
+use crate::GameState;
+
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(DefaultSpatialScale(SpatialScale::new(SPATIAL_SCALE)))
+            .init_resource::<Sounds>()
+            .add_systems(Startup, spawn_music)
+            .add_systems(PostStartup, add_listener)
+            .add_systems(Update, (crossfade_music, click_sound))
+            .add_systems(
+                Update,
+                (collision_sound, warning_horns, docking_chimes).run_if(in_state(GameState::Game)),
+            )
+            .add_observer(departure_horn);
+    }
+}
+
+/// world units are scaled by this before the distance falloff, the map is large
+const SPATIAL_SCALE: f32 = 0.05;
+/// seconds a crossfade between the menu and game music takes
+const CROSSFADE_SECS: f32 = 2.0;
+/// ships sound their horn when a conflict is this close
+const HORN_SECS: f32 = 3.0;
+
+/// tension over which a music layer fades in, above its threshold
+const LAYER_FADE: f32 = 0.2;
+
+const MENU_MUSIC: &str = "audio/music/harbour_calm.wav";
+/// stems of the game music, all the same length so they loop in sync,
+/// each joins in once the tension reaches its threshold
+const GAME_MUSIC: [(&str, f32); 3] = [
+    ("audio/music/harbour.wav", 0.0),
+    ("audio/music/harbour_drums.wav", 0.3),
+    ("audio/music/harbour_strings.wav", 0.65),
+];
+
+/// Sound effects, loaded once at startup.
+#[derive(Resource)]
+struct Sounds {
+    horn: Handle<AudioSource>,
+    crash: Handle<AudioSource>,
+    chime: Handle<AudioSource>,
+    click: Handle<AudioSource>,
+}
+
+impl FromWorld for Sounds {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        Sounds {
+            horn: asset_server.load("audio/sfx/horn.wav"),
+            crash: asset_server.load("audio/sfx/crash.wav"),
+            chime: asset_server.load("audio/sfx/chime.wav"),
+            click: asset_server.load("audio/sfx/click.wav"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MusicTrack {
+    Menu,
+    Game,
+}
+
+/// A looping music track, faded in while its part of the game is shown.
+#[derive(Component)]
+struct Music {
+    track: MusicTrack,
+    /// 0..1, multiplied with the music volume
+    fade: f32,
+    /// [`Tension`] from which this layer plays, 0 for tracks that always play
+    threshold: f32,
+}
+
+impl Music {
+    fn layer_gain(&self, tension: f32) -> f32 {
+        if self.threshold <= 0.0 {
+            return 1.0;
+        }
+        ((tension - self.threshold) / LAYER_FADE).clamp(0.0, 1.0)
+    }
+}
+
+fn spawn_music(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let menu = [(MusicTrack::Menu, MENU_MUSIC, 0.0)];
+    let game = GAME_MUSIC.map(|(path, threshold)| (MusicTrack::Game, path, threshold));
+    for (track, path, threshold) in menu.into_iter().chain(game) {
+        commands.spawn((
+            Music { track, fade: 0.0, threshold },
+            AudioPlayer::new(asset_server.load(path)),
+            PlaybackSettings::LOOP.with_volume(Volume::ZERO),
+        ));
+    }
+}
+
+/// the main camera is where the player hears from
+fn add_listener(mut commands: Commands, camera: Single<Entity, With<PanOrbitState>>) {
+    commands.entity(*camera).insert(SpatialListener::new(4.0));
+}
+
+/// fades the track of the current state in and the other one out,
+/// on real time so it keeps going while the game is paused.
+/// Layers of the game music follow the [`Tension`].
+fn crossfade_music(
+    time: Res<Time<Real>>,
+    state: Res<State<GameState>>,
+    save: Res<SaveData>,
+    tension: Res<Tension>,
+    mut music: Query<(&mut Music, Option<&AudioSink>)>,
+) {
+    let wanted = match state.get() {
+        GameState::Game => MusicTrack::Game,
+        _ => MusicTrack::Menu,
+    };
+    let step = time.delta_secs() / CROSSFADE_SECS;
+    for (mut music, sink) in music.iter_mut() {
+        let target = if music.track == wanted { 1.0 } else { 0.0 };
+        music.fade = if music.fade < target {
+            (music.fade + step).min(target)
+        } else {
+            (music.fade - step).max(target)
+        };
+        if let Some(sink) = sink {
+            let layer = music.layer_gain(tension.0);
+            sink.set_volume(music.fade * layer * save.settings.music_gain());
+        }
+    }
+}
+
+/// one-shot sound at a place in the world, or attached to a ship so it moves along
+fn spatial_sound(sound: Handle<AudioSource>, settings: &Settings) -> impl Bundle {
+    (
+        AudioPlayer::new(sound),
+        PlaybackSettings::DESPAWN
+            .with_spatial(true)
+            .with_volume(Volume::new(settings.sfx_gain())),
+    )
+}
+
+fn click_sound(
+    mut commands: Commands,
+    sounds: Res<Sounds>,
+    save: Res<SaveData>,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<Button>)>,
+) {
+    for interaction in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            commands.spawn((
+                AudioPlayer::new(sounds.click.clone()),
+                PlaybackSettings::DESPAWN.with_volume(Volume::new(save.settings.sfx_gain())),
+            ));
+        }
+    }
+}
+
+fn collision_sound(
+    mut commands: Commands,
+    mut collisions: EventReader<CollisionEvent>,
+    sounds: Res<Sounds>,
+    save: Res<SaveData>,
+    ships: Query<&GlobalTransform, With<ShipRoot>>,
+) {
+    // collisions are reported for both orders of a pair, one crash is enough
+    let mut crashes = HashSet::new();
+    for ev in collisions.read() {
+        if !crashes.insert((ev.entity_a.min(ev.entity_b), ev.entity_a.max(ev.entity_b))) {
+            continue;
+        }
+        let Ok(ship) = ships.get(ev.entity_a).or_else(|_| ships.get(ev.entity_b)) else {
+            continue;
+        };
+        commands.spawn((
+            spatial_sound(sounds.crash.clone(), &save.settings),
+            Transform::from_translation(ship.translation()),
+        ));
+    }
+}
+
+/// a ship leaving port blows its horn
+fn departure_horn(
+    trigger: Trigger<OnAdd, ShipRoot>,
+    mut commands: Commands,
+    sounds: Res<Sounds>,
+    save: Res<SaveData>,
+) {
+    commands.entity(trigger.entity()).with_child((
+        spatial_sound(sounds.horn.clone(), &save.settings),
+        Transform::default(),
+    ));
+}
+
+/// both ships of a new close conflict warn each other
+fn warning_horns(
+    mut commands: Commands,
+    forecast: Res<ConflictForecast>,
+    sounds: Res<Sounds>,
+    save: Res<SaveData>,
+    mut warned: Local<HashSet<(Entity, Entity)>>,
+) {
+    let close: HashSet<(Entity, Entity)> = forecast
+        .conflicts
+        .iter()
+        .filter(|conflict| conflict.in_secs <= HORN_SECS)
+        .map(|conflict| (conflict.entity_a, conflict.entity_b))
+        .collect();
+    for &(a, b) in close.difference(&warned) {
+        for ship in [a, b] {
+            if let Some(mut ship) = commands.get_entity(ship) {
+                ship.with_child((
+                    spatial_sound(sounds.horn.clone(), &save.settings),
+                    Transform::default(),
+                ));
+            }
+        }
+    }
+    *warned = close;
+}
+
+/// chimes when a ship gets back to the start of its route
+fn docking_chimes(
+    mut commands: Commands,
+    sounds: Res<Sounds>,
+    save: Res<SaveData>,
+    ships: Query<(Entity, &PathFollow), With<ShipRoot>>,
+    mut progress: Local<HashMap<Entity, f32>>,
+) {
+    let mut seen = HashMap::new();
+    for (entity, path_follow) in ships.iter() {
+        let half_route = path_follow.curve.segments().len() as f32 / 2.0;
+        if progress.get(&entity).is_some_and(|before| path_follow.t < before - half_route) {
+            commands.entity(entity).with_child((
+                spatial_sound(sounds.chime.clone(), &save.settings),
+                Transform::default(),
+            ));
+        }
+        seen.insert(entity, path_follow.t);
+    }
+    *progress = seen;
+}
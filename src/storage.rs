@@ -0,0 +1,394 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::actions::Bindings;
+use crate::{GameState, Score};
+
+/// Layout version of [`SaveData`]. Bump this when the format changes and
+/// teach [`migrate`] how to read the previous version.
+pub const SAVE_VERSION: u32 = 1;
+
+const MAX_HIGH_SCORES: usize = 10;
+
+/// name of the save file on native, key in localStorage on wasm
+const SAVE_KEY: &str = "haverie_save";
+
+pub struct StoragePlugin;
+
+impl Plugin for StoragePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SaveData::load())
+            .add_systems(OnEnter(GameState::GameOver), record_high_score)
+            .add_systems(
+                Last,
+                // loading is not a change worth writing back
+                write_save.run_if(resource_changed::<SaveData>.and(not(resource_added::<SaveData>))),
+            );
+    }
+}
+
+/// Everything that survives a restart of the game.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+pub struct SaveData {
+    pub version: u32,
+    /// best scores, highest first
+    pub high_scores: Vec<u32>,
+    pub settings: Settings,
+    pub unlocked_levels: Vec<String>,
+}
+
+/// Player preferences. Missing fields fall back to their defaults, so new
+/// settings can be added without bumping [`SAVE_VERSION`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Settings {
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub bindings: Bindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.0,
+            music_volume: 0.8,
+            sfx_volume: 0.8,
+            bindings: Bindings::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// volume music plays at
+    pub fn music_gain(&self) -> f32 {
+        self.master_volume * self.music_volume
+    }
+
+    /// volume sound effects play at
+    pub fn sfx_gain(&self) -> f32 {
+        self.master_volume * self.sfx_volume
+    }
+
+    pub fn volume(&self, channel: VolumeChannel) -> f32 {
+        match channel {
+            VolumeChannel::Master => self.master_volume,
+            VolumeChannel::Music => self.music_volume,
+            VolumeChannel::Sfx => self.sfx_volume,
+        }
+    }
+
+    pub fn volume_mut(&mut self, channel: VolumeChannel) -> &mut f32 {
+        match channel {
+            VolumeChannel::Master => &mut self.master_volume,
+            VolumeChannel::Music => &mut self.music_volume,
+            VolumeChannel::Sfx => &mut self.sfx_volume,
+        }
+    }
+}
+
+/// The separately adjustable volumes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VolumeChannel {
+    Master,
+    Music,
+    Sfx,
+}
+
+impl VolumeChannel {
+    pub const ALL: [VolumeChannel; 3] = [VolumeChannel::Master, VolumeChannel::Music, VolumeChannel::Sfx];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            VolumeChannel::Master => "Master volume",
+            VolumeChannel::Music => "Music volume",
+            VolumeChannel::Sfx => "Effects volume",
+        }
+    }
+}
+
+impl Default for SaveData {
+    fn default() -> Self {
+        Self {
+            version: SAVE_VERSION,
+            high_scores: Vec::new(),
+            settings: Settings::default(),
+            unlocked_levels: Vec::new(),
+        }
+    }
+}
+
+impl SaveData {
+    /// Reads the save from disk / localStorage, falls back to a fresh save.
+    /// A save that can't be read is copied to a backup first, so the fresh
+    /// save doesn't destroy it once it gets written.
+    pub fn load() -> Self {
+        let Some(raw) = backend::read() else {
+            return Self::default();
+        };
+        match migrate(&raw) {
+            Ok(save) => save,
+            Err(err) => {
+                warn!("could not read save data, starting fresh: {err}");
+                if let Err(err) = backend::backup(&raw) {
+                    warn!("could not back up the unreadable save data: {err}");
+                }
+                Self::default()
+            }
+        }
+    }
+
+    pub fn best_score(&self) -> Option<u32> {
+        self.high_scores.first().copied()
+    }
+
+    /// inserts a score into the table, keeping only the best [`MAX_HIGH_SCORES`]
+    pub fn submit_score(&mut self, score: u32) {
+        let index = self.high_scores.partition_point(|s| *s >= score);
+        self.high_scores.insert(index, score);
+        self.high_scores.truncate(MAX_HIGH_SCORES);
+    }
+
+    pub fn is_unlocked(&self, level: &str) -> bool {
+        self.unlocked_levels.iter().any(|l| l == level)
+    }
+
+    pub fn unlock_level(&mut self, level: &str) {
+        if !self.is_unlocked(level) {
+            self.unlocked_levels.push(level.to_string());
+        }
+    }
+}
+
+/// Only the version field, used to find out how to read the rest of the file.
+#[derive(Deserialize)]
+struct VersionProbe {
+    #[serde(default)]
+    version: u32,
+}
+
+/// Saves written before the version field, with high scores and unlocked
+/// levels but no settings.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct SaveDataV0 {
+    high_scores: Vec<u32>,
+    unlocked_levels: Vec<String>,
+}
+
+impl From<SaveDataV0> for SaveData {
+    fn from(old: SaveDataV0) -> Self {
+        let mut save = SaveData {
+            unlocked_levels: old.unlocked_levels,
+            ..default()
+        };
+        for score in old.high_scores {
+            save.submit_score(score);
+        }
+        save
+    }
+}
+
+/// Parses a save of any known version and upgrades it to [`SAVE_VERSION`].
+/// Every older version gets an arm that reads its layout and converts it
+/// into the next one.
+fn migrate(raw: &str) -> Result<SaveData, String> {
+    let probe: VersionProbe = ron::from_str(raw).map_err(|e| e.to_string())?;
+    match probe.version {
+        0 => ron::from_str::<SaveDataV0>(raw).map(SaveData::from).map_err(|e| e.to_string()),
+        SAVE_VERSION => ron::from_str(raw).map_err(|e| e.to_string()),
+        version => Err(format!("unknown save version {version}")),
+    }
+}
+
+fn record_high_score(score: Res<Score>, mut save: ResMut<SaveData>) {
+    save.submit_score(score.seconds());
+}
+
+fn write_save(save: Res<SaveData>) {
+    let raw = match ron::ser::to_string_pretty(&*save, ron::ser::PrettyConfig::default()) {
+        Ok(raw) => raw,
+        Err(err) => {
+            warn!("could not serialize save data: {err}");
+            return;
+        }
+    };
+    if let Err(err) = backend::write(&raw) {
+        warn!("could not write save data: {err}");
+    }
+}
+
+/// Stores the save as a file in the platform config directory.
+#[cfg(not(target_arch = "wasm32"))]
+mod backend {
+    use std::path::PathBuf;
+
+    /// where tests keep their saves instead of the player's config directory
+    #[cfg(test)]
+    pub static TEST_DIR: std::sync::Mutex<Option<PathBuf>> = std::sync::Mutex::new(None);
+
+    fn dir() -> Option<PathBuf> {
+        #[cfg(test)]
+        if let Some(dir) = TEST_DIR.lock().unwrap().clone() {
+            return Some(dir);
+        }
+        dirs::config_dir().map(|dir| dir.join("haverie"))
+    }
+
+    pub fn path() -> Option<PathBuf> {
+        dir().map(|dir| dir.join(format!("{}.ron", super::SAVE_KEY)))
+    }
+
+    pub fn backup_path() -> Option<PathBuf> {
+        dir().map(|dir| dir.join(format!("{}.ron.bak", super::SAVE_KEY)))
+    }
+
+    pub fn read() -> Option<String> {
+        std::fs::read_to_string(path()?).ok()
+    }
+
+    fn write_to(path: Option<PathBuf>, raw: &str) -> Result<(), String> {
+        let path = path.ok_or("no config directory on this platform")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(path, raw).map_err(|e| e.to_string())
+    }
+
+    pub fn write(raw: &str) -> Result<(), String> {
+        write_to(path(), raw)
+    }
+
+    pub fn backup(raw: &str) -> Result<(), String> {
+        write_to(backup_path(), raw)
+    }
+}
+
+/// Stores the save in the browser's localStorage.
+#[cfg(target_arch = "wasm32")]
+mod backend {
+    fn storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+
+    pub fn read() -> Option<String> {
+        storage()?.get_item(super::SAVE_KEY).ok()?
+    }
+
+    fn write_to(key: &str, raw: &str) -> Result<(), String> {
+        let storage = storage().ok_or("localStorage is not available")?;
+        storage.set_item(key, raw).map_err(|e| format!("{e:?}"))
+    }
+
+    pub fn write(raw: &str) -> Result<(), String> {
+        write_to(super::SAVE_KEY, raw)
+    }
+
+    pub fn backup(raw: &str) -> Result<(), String> {
+        write_to(&format!("{}.bak", super::SAVE_KEY), raw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn serialize(save: &SaveData) -> String {
+        ron::ser::to_string(save).unwrap()
+    }
+
+    #[test]
+    fn migrate_reads_current_version() {
+        let mut save = SaveData::default();
+        save.submit_score(42);
+        save.unlock_level("open_sea");
+
+        let loaded = migrate(&serialize(&save)).unwrap();
+        assert_eq!(loaded.version, SAVE_VERSION);
+        assert_eq!(loaded.high_scores, vec![42]);
+        assert!(loaded.is_unlocked("open_sea"));
+    }
+
+    #[test]
+    fn migrate_fills_in_missing_settings() {
+        let raw = format!("(version: {SAVE_VERSION}, high_scores: [], settings: (music_volume: 0.5), unlocked_levels: [])");
+        let loaded = migrate(&raw).unwrap();
+        assert_eq!(loaded.settings.music_volume, 0.5);
+        assert_eq!(loaded.settings.master_volume, Settings::default().master_volume);
+    }
+
+    #[test]
+    fn migrate_rejects_unknown_versions() {
+        let raw = serialize(&SaveData {
+            version: SAVE_VERSION + 1,
+            ..default()
+        });
+        assert!(migrate(&raw).is_err());
+        assert!(migrate("not a save").is_err());
+    }
+
+    #[test]
+    fn migrate_upgrades_saves_without_version() {
+        let loaded = migrate("(high_scores: [5, 50], unlocked_levels: [\"open_sea\"])").unwrap();
+        assert_eq!(loaded.version, SAVE_VERSION);
+        assert_eq!(loaded.high_scores, vec![50, 5]);
+        assert!(loaded.is_unlocked("open_sea"));
+        assert_eq!(loaded.settings.music_volume, Settings::default().music_volume);
+    }
+
+    #[test]
+    fn unreadable_save_survives_startup() {
+        let dir = std::env::temp_dir().join(format!("haverie-save-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        *backend::TEST_DIR.lock().unwrap() = Some(dir.clone());
+        let raw = serialize(&SaveData {
+            version: SAVE_VERSION + 1,
+            ..default()
+        });
+        std::fs::write(backend::path().unwrap(), &raw).unwrap();
+
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, bevy::state::app::StatesPlugin))
+            .init_state::<GameState>()
+            .add_plugins(StoragePlugin);
+        app.update();
+        app.update();
+
+        // starting fresh neither overwrites the file nor loses it later
+        assert_eq!(std::fs::read_to_string(backend::path().unwrap()).unwrap(), raw);
+        assert_eq!(std::fs::read_to_string(backend::backup_path().unwrap()).unwrap(), raw);
+
+        app.world_mut().resource_mut::<SaveData>().submit_score(7);
+        app.update();
+        let written = migrate(&std::fs::read_to_string(backend::path().unwrap()).unwrap()).unwrap();
+        assert_eq!(written.high_scores, vec![7]);
+        assert_eq!(std::fs::read_to_string(backend::backup_path().unwrap()).unwrap(), raw);
+
+        *backend::TEST_DIR.lock().unwrap() = None;
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn submit_score_keeps_best_first() {
+        let mut save = SaveData::default();
+        for score in [10, 30, 20, 30] {
+            save.submit_score(score);
+        }
+        assert_eq!(save.high_scores, vec![30, 30, 20, 10]);
+        assert_eq!(save.best_score(), Some(30));
+    }
+
+    #[test]
+    fn submit_score_drops_worst_when_full() {
+        let mut save = SaveData::default();
+        for score in 1..=MAX_HIGH_SCORES as u32 {
+            save.submit_score(score);
+        }
+        save.submit_score(0);
+        assert_eq!(save.high_scores.len(), MAX_HIGH_SCORES);
+        assert!(!save.high_scores.contains(&0));
+
+        save.submit_score(100);
+        assert_eq!(save.best_score(), Some(100));
+        assert!(!save.high_scores.contains(&1));
+    }
+}
@@ -0,0 +1,195 @@
+use bevy::prelude::*;
+use bevy::render::camera::ScalingMode;
+use bevy::utils::HashSet;
+use crate::actions::{Action, ActionState};
+use crate::camera::{CameraInputSet, CameraSet, PanOrbitState};
+use crate::map::MAP_SIZE;
+use crate::radar::RadarContact;
+use crate::ship::ShipRoot;
+use crate::GameState;
+
+pub struct TacticalPlugin;
+
+impl Plugin for TacticalPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TacticalView>()
+            .configure_sets(Update, CameraInputSet.run_if(tactical_view_off))
+            .add_systems(OnEnter(GameState::GameOver), leave_tactical_view)
+            .add_systems(Update, toggle_tactical_view.run_if(in_state(GameState::Game)))
+            .add_systems(
+                Update,
+                (apply_tactical_view, (tactical_gizmos, update_ship_labels))
+                    .chain()
+                    .after(CameraSet),
+            );
+    }
+}
+
+/// seconds the switch between the views takes
+const TRANSITION_SECS: f32 = 0.8;
+/// route and overlay line width in the normal and in the tactical view
+const LINE_WIDTH: f32 = 2.0;
+const TACTICAL_LINE_WIDTH: f32 = 5.0;
+
+/// Orthographic view of the whole map from straight above. While it is
+/// shown the camera ignores player input, the pan-orbit view is kept for later.
+#[derive(Resource, Default, Debug)]
+pub struct TacticalView {
+    pub active: bool,
+    /// 0 in the normal view, 1 in the tactical view
+    blend: f32,
+}
+
+impl TacticalView {
+    /// fully switched to the orthographic projection
+    pub fn is_settled(&self) -> bool {
+        self.blend >= 1.0
+    }
+}
+
+/// Name tag that follows a ship around in the tactical view.
+#[derive(Component)]
+struct ShipLabel(Entity);
+
+fn tactical_view_off(view: Res<TacticalView>) -> bool {
+    !view.active && view.blend <= 0.0
+}
+
+fn toggle_tactical_view(actions: Res<ActionState>, mut view: ResMut<TacticalView>) {
+    if actions.just_pressed(Action::TacticalView) {
+        view.active = !view.active;
+    }
+}
+
+/// the crash close-up should be seen in the normal view
+fn leave_tactical_view(mut view: ResMut<TacticalView>) {
+    view.active = false;
+}
+
+/// the perspective view that shows as much as the orthographic one
+fn tactical_transform(fov: f32) -> Transform {
+    let height = MAP_SIZE as f32 / 2.0 / (fov / 2.0).tan();
+    Transform::from_xyz(0.0, height, 0.0).looking_at(Vec3::ZERO, Vec3::NEG_Z)
+}
+
+fn orthographic() -> Projection {
+    Projection::Orthographic(OrthographicProjection {
+        scaling_mode: ScalingMode::AutoMin {
+            min_width: MAP_SIZE as f32,
+            min_height: MAP_SIZE as f32,
+        },
+        far: 2000.0,
+        ..OrthographicProjection::default_3d()
+    })
+}
+
+/// Flies the camera up to the map view in perspective, then switches to the
+/// orthographic projection. Going back switches first and then flies down.
+/// Picking uses the camera's projection, so it works in both.
+fn apply_tactical_view(
+    time: Res<Time>,
+    mut view: ResMut<TacticalView>,
+    mut camera: Single<(&PanOrbitState, &mut Transform, &mut Projection)>,
+) {
+    if !view.active && view.blend <= 0.0 {
+        return;
+    }
+    let (state, transform, projection) = &mut *camera;
+
+    let step = time.delta_secs() / TRANSITION_SECS;
+    view.blend = if view.active {
+        (view.blend + step).min(1.0)
+    } else {
+        (view.blend - step).max(0.0)
+    };
+
+    let settled = view.is_settled();
+    match (settled, &**projection) {
+        (true, Projection::Perspective(_)) => **projection = orthographic(),
+        (false, Projection::Orthographic(_)) => **projection = Projection::Perspective(default()),
+        _ => {}
+    }
+
+    let fov = PerspectiveProjection::default().fov;
+    let from = state.transform();
+    let to = tactical_transform(fov);
+    let t = view.blend * view.blend * (3.0 - 2.0 * view.blend);
+    transform.translation = from.translation.lerp(to.translation, t);
+    transform.rotation = from.rotation.slerp(to.rotation, t);
+}
+
+/// thicker lines so routes read well from far above
+fn tactical_gizmos(view: Res<TacticalView>, mut config_store: ResMut<GizmoConfigStore>) {
+    if !view.is_changed() {
+        return;
+    }
+    let (config, _) = config_store.config_mut::<DefaultGizmoConfigGroup>();
+    config.line_width = LINE_WIDTH.lerp(TACTICAL_LINE_WIDTH, view.blend);
+    // drawn on top of the water and ships
+    config.depth_bias = if view.is_settled() { -1.0 } else { 0.0 };
+}
+
+fn update_ship_labels(
+    mut commands: Commands,
+    view: Res<TacticalView>,
+    asset_server: Res<AssetServer>,
+    camera: Single<(&Camera, &GlobalTransform), With<PanOrbitState>>,
+    ships: Query<(Entity, &GlobalTransform, &Name, &RadarContact), With<ShipRoot>>,
+    mut labels: Query<(Entity, &ShipLabel, &mut Node)>,
+) {
+    let (camera, camera_transform) = *camera;
+    let show = view.is_settled();
+    let screen_position = |ship: &GlobalTransform| {
+        camera
+            .world_to_viewport(camera_transform, ship.translation())
+            .ok()
+            .map(|p| (Val::Px(p.x + 8.0), Val::Px(p.y - 8.0)))
+    };
+
+    let mut labelled = HashSet::new();
+    for (label_entity, label, mut node) in labels.iter_mut() {
+        let position = ships
+            .get(label.0)
+            .ok()
+            .filter(|(.., contact)| show && contact.detected)
+            .and_then(|(_, ship, ..)| screen_position(ship));
+        match position {
+            Some((left, top)) => {
+                node.left = left;
+                node.top = top;
+                labelled.insert(label.0);
+            }
+            None => commands.entity(label_entity).despawn_recursive(),
+        }
+    }
+
+    if !show {
+        return;
+    }
+    let font = asset_server.load("fonts/Kenney Mini Square.ttf");
+    for (entity, ship, name, contact) in ships.iter() {
+        if !contact.detected || labelled.contains(&entity) {
+            continue;
+        }
+        let Some((left, top)) = screen_position(ship) else {
+            continue;
+        };
+        commands.spawn((
+            ShipLabel(entity),
+            StateScoped(GameState::Game),
+            Text::new(format!("{name} {}", entity.index())),
+            TextFont {
+                font: font.clone(),
+                font_size: 14.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+            Node {
+                position_type: PositionType::Absolute,
+                left,
+                top,
+                ..default()
+            },
+        ));
+    }
+}
@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+use crate::camera::PanOrbitState;
+use crate::collision::{ConflictForecast, FORECAST_HORIZON};
+use crate::radar::{RadarContact, RadarSet};
+use crate::ship::ShipRoot;
+use crate::GameState;
+
+pub struct TensionPlugin;
+
+impl Plugin for TensionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tension>()
+            .add_systems(OnEnter(GameState::Game), reset_tension)
+            .add_systems(
+                Update,
+                update_tension.after(RadarSet).run_if(in_state(GameState::Game)),
+            );
+    }
+}
+
+/// this many ships on screen is as crowded as it gets
+const CROWDED: f32 = 8.0;
+/// how much a full screen adds, an imminent conflict adds up to 1
+const CROWD_WEIGHT: f32 = 0.6;
+/// tension builds fast and wears off slowly, see `smooth_nudge`
+const RISE_RATE: f32 = 2.0;
+const FALL_RATE: f32 = 0.3;
+
+/// How dangerous the sea looks right now, 0 when calm and 1 when a crash
+/// is about to happen. Drives the music and can drive other effects.
+#[derive(Resource, Default, Debug)]
+pub struct Tension(pub f32);
+
+fn reset_tension(mut tension: ResMut<Tension>) {
+    tension.0 = 0.0;
+}
+
+fn update_tension(
+    time: Res<Time>,
+    forecast: Res<ConflictForecast>,
+    camera: Single<(&Camera, &GlobalTransform), With<PanOrbitState>>,
+    ships: Query<(&GlobalTransform, &RadarContact), With<ShipRoot>>,
+    mut tension: ResMut<Tension>,
+) {
+    let (camera, camera_transform) = *camera;
+    let on_screen = ships
+        .iter()
+        .filter(|(_, contact)| contact.detected)
+        .filter_map(|(ship, _)| camera.world_to_ndc(camera_transform, ship.translation()))
+        .filter(|ndc| ndc.x.abs() <= 1.0 && ndc.y.abs() <= 1.0 && (0.0..=1.0).contains(&ndc.z))
+        .count();
+    let crowd = (on_screen as f32 / CROWDED).min(1.0);
+
+    let urgency = forecast
+        .soonest()
+        .map_or(0.0, |conflict| 1.0 - conflict.in_secs / FORECAST_HORIZON);
+
+    let target = (crowd * CROWD_WEIGHT + urgency).clamp(0.0, 1.0);
+    let rate = if target > tension.0 { RISE_RATE } else { FALL_RATE };
+    tension.0.smooth_nudge(&target, rate, time.delta_secs());
+}
@@ -1,5 +1,12 @@
 use bevy::prelude::*;
-use crate::{setup, GameState};
+use bevy::ui::FocusPolicy;
+use crate::{setup, GameState, Score};
+use crate::actions::{Action, ActionState, Rebinding, SwappedBinding};
+use crate::config::ConfigAssets;
+use crate::difficulty::Difficulty;
+use crate::level::{CurrentLevel, LevelList};
+use crate::mode::{CurrentMode, GameModeList, Lives};
+use crate::storage::{SaveData, VolumeChannel};
 
 pub struct UiPlugin;
 const MARGIN: Val = Val::Px(12.);
@@ -12,29 +19,89 @@ const PRESSED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
         app
+            .add_systems(OnEnter(GameState::Menu), spawn_main_menu)
             .add_systems(OnEnter(GameState::GameOver), spawn_game_over_menu)
-            .add_systems(OnEnter(GameState::Game), cleanup_ui)
-            .add_systems(Update, play_again_button_handler.run_if(in_state(GameState::GameOver)))
+            .add_systems(OnEnter(GameState::Game), spawn_hud)
+            .add_systems(OnExit(GameState::Menu), close_settings_menu)
+            .add_systems(OnExit(GameState::Game), close_settings_menu)
+            .add_systems(OnExit(GameState::GameOver), close_settings_menu)
+            .add_systems(
+                Update,
+                state_button_handler.run_if(in_state(GameState::Menu).or(in_state(GameState::GameOver))),
+            )
+            .add_systems(Update, (mode_button_handler, level_button_handler).run_if(in_state(GameState::Menu)))
+            .add_systems(Update, update_hud.run_if(in_state(GameState::Game)))
+            .add_systems(
+                Update,
+                (
+                    toggle_settings_menu,
+                    settings_button_handler,
+                    (update_binding_labels, update_volume_labels),
+                )
+                    .chain()
+                    .run_if(not(in_state(GameState::Loading))),
+            )
         ;
     }
 }
 
-fn play_again_button_handler(
+/// Text in the top left corner showing how the session is going.
+#[derive(Component)]
+struct Hud;
+
+/// Switches to its state when pressed, e.g. starts a session from the menu.
+#[derive(Component)]
+struct StateButton(GameState);
+
+/// Picks the game mode with this id for the next session.
+#[derive(Component)]
+struct ModeButton(String);
+
+/// Picks the level with this id for the next session.
+#[derive(Component)]
+struct LevelButton(String);
+
+/// Overlay listing every [`Action`] with its bindings, opened with [`Action::Settings`].
+#[derive(Component)]
+struct SettingsMenu;
+
+/// Waits for a new binding for its action when pressed.
+#[derive(Component)]
+struct RebindButton(Action);
+
+#[derive(Component)]
+struct ResetBindingsButton;
+
+/// Line under the bindings telling which action a rebind took its button from.
+#[derive(Component)]
+struct BindingNotice;
+
+/// Changes a volume by `step` when pressed.
+#[derive(Component)]
+struct VolumeButton {
+    channel: VolumeChannel,
+    step: f32,
+}
+
+#[derive(Component)]
+struct VolumeLabel(VolumeChannel);
+
+fn state_button_handler(
     mut interaction_query: Query<
         (
             &Interaction,
+            &StateButton,
             &mut BackgroundColor,
             &mut BorderColor,
-            &Children,
         ),
-        (Changed<Interaction>, With<Button>),
+        Changed<Interaction>,
     >,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
-    for (interaction, mut color, mut border_color, children) in &mut interaction_query {
+    for (interaction, StateButton(state), mut color, mut border_color) in &mut interaction_query {
         match *interaction {
             Interaction::Pressed => {
-                next_state.set(GameState::Game)
+                next_state.set(state.clone())
             }
             Interaction::Hovered => {
                 *color = HOVERED_BUTTON.into();
@@ -48,51 +115,524 @@ fn play_again_button_handler(
     }
 }
 
-/// remove all ui nodes
-fn cleanup_ui(mut commands: Commands, query: Query<Entity, With<Node>>) {
-    for entity in query.iter() {
-        commands.entity(entity).despawn_recursive();
+fn mode_button_handler(
+    config: Res<ConfigAssets>,
+    mode_lists: Res<Assets<GameModeList>>,
+    mut current_mode: ResMut<CurrentMode>,
+    changed: Query<&Interaction, (Changed<Interaction>, With<ModeButton>)>,
+    mut buttons: Query<(&Interaction, &ModeButton, &mut BackgroundColor, &mut BorderColor)>,
+) {
+    if changed.is_empty() {
+        return;
+    }
+    for (interaction, ModeButton(id), ..) in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            current_mode.0 = id.clone();
+        }
+    }
+    let selected = current_mode.get(&config, &mode_lists).map(|mode| mode.id.as_str());
+    for (interaction, ModeButton(id), mut color, mut border_color) in buttons.iter_mut() {
+        *color = choice_color(selected == Some(id.as_str())).into();
+        border_color.0 = match interaction {
+            Interaction::None => Color::BLACK,
+            _ => Color::WHITE,
+        };
+    }
+}
+
+fn level_button_handler(
+    config: Res<ConfigAssets>,
+    level_lists: Res<Assets<LevelList>>,
+    mut current_level: ResMut<CurrentLevel>,
+    changed: Query<&Interaction, (Changed<Interaction>, With<LevelButton>)>,
+    mut buttons: Query<(&Interaction, &LevelButton, &mut BackgroundColor, &mut BorderColor)>,
+) {
+    if changed.is_empty() {
+        return;
+    }
+    for (interaction, LevelButton(id), ..) in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            current_level.0 = id.clone();
+        }
+    }
+    let selected = current_level.get(&config, &level_lists).map(|level| level.id.as_str());
+    for (interaction, LevelButton(id), mut color, mut border_color) in buttons.iter_mut() {
+        *color = choice_color(selected == Some(id.as_str())).into();
+        border_color.0 = match interaction {
+            Interaction::None => Color::BLACK,
+            _ => Color::WHITE,
+        };
+    }
+}
+
+fn choice_color(selected: bool) -> Color {
+    if selected { PRESSED_BUTTON } else { NORMAL_BUTTON }
+}
+
+/// small pill button for picking one of a few options
+fn choice_button(selected: bool) -> impl Bundle {
+    (
+        Button,
+        Node {
+            padding: UiRect::axes(Val::Px(16.0), Val::Px(8.0)),
+            border: UiRect::all(Val::Px(3.0)),
+            ..default()
+        },
+        BorderColor(Color::BLACK),
+        BorderRadius::MAX,
+        BackgroundColor(choice_color(selected)),
+    )
+}
+
+/// large round button that switches to `state`
+fn state_button(state: GameState) -> impl Bundle {
+    (
+        Button,
+        StateButton(state),
+        Node {
+            width: Val::Px(150.0),
+            height: Val::Px(65.0),
+            border: UiRect::all(Val::Px(5.0)),
+            // horizontally center child text
+            justify_content: JustifyContent::Center,
+            // vertically center child text
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BorderColor(Color::BLACK),
+        BorderRadius::MAX,
+        BackgroundColor(NORMAL_BUTTON),
+    )
+}
+
+fn menu_text(text: impl Into<String>, font: &Handle<Font>, font_size: f32) -> impl Bundle {
+    (
+        Text::new(text),
+        TextFont {
+            font: font.clone(),
+            font_size,
+            ..default()
+        },
+        TextColor(Color::srgb(0.9, 0.9, 0.9)),
+    )
+}
+
+/// full screen column that the menus are laid out in
+fn menu_root(state: GameState) -> impl Bundle {
+    (
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: MARGIN,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        BackgroundColor(Color::BLACK),
+        StateScoped(state),
+    )
+}
+
+/// Title screen where the mode and level of the next session are picked.
+fn spawn_main_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<ConfigAssets>,
+    mode_lists: Res<Assets<GameModeList>>,
+    current_mode: Res<CurrentMode>,
+    level_lists: Res<Assets<LevelList>>,
+    current_level: Res<CurrentLevel>,
+    save: Res<SaveData>,
+) {
+    let font = asset_server.load("fonts/Kenney Mini Square.ttf");
+    let modes = mode_lists.get(&config.modes).map_or(&[][..], |list| &list.modes[..]);
+    let selected = current_mode.get(&config, &mode_lists).map(|mode| mode.id.as_str());
+    let levels = level_lists.get(&config.levels).map_or(&[][..], |list| &list.levels[..]);
+    let selected_level = current_level.get(&config, &level_lists).map(|level| level.id.as_str());
+    commands.spawn(menu_root(GameState::Menu)).with_children(|parent| {
+        parent.spawn(menu_text("Haverie", &font, 48.0));
+        parent
+            .spawn(Node {
+                column_gap: MARGIN,
+                ..default()
+            })
+            .with_children(|row| {
+                for mode in modes {
+                    row.spawn((choice_button(selected == Some(mode.id.as_str())), ModeButton(mode.id.clone())))
+                        .with_child(menu_text(mode.name.clone(), &font, 22.0));
+                }
+            });
+        parent
+            .spawn(Node {
+                column_gap: MARGIN,
+                ..default()
+            })
+            .with_children(|row| {
+                for level in levels {
+                    if level.is_open(&save) {
+                        row.spawn((choice_button(selected_level == Some(level.id.as_str())), LevelButton(level.id.clone())))
+                            .with_child(menu_text(level.name.clone(), &font, 22.0));
+                    } else {
+                        let label = format!("{} ({} to unlock)", level.name, level.unlock_score);
+                        row.spawn(menu_text(label, &font, 22.0))
+                            .insert(TextColor(Color::srgb(0.45, 0.45, 0.45)));
+                    }
+                }
+            });
+        parent
+            .spawn(state_button(GameState::Game))
+            .with_child(menu_text("Play", &font, 33.0));
+    });
+}
+
+fn spawn_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        Hud,
+        StateScoped(GameState::Game),
+        Text::default(),
+        TextFont {
+            font: asset_server.load("fonts/Kenney Mini Square.ttf"),
+            font_size: 22.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.9, 0.9, 0.9)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: MARGIN,
+            left: MARGIN,
+            ..default()
+        },
+    ));
+}
+
+fn update_hud(
+    mut hud: Single<&mut Text, With<Hud>>,
+    score: Res<Score>,
+    difficulty: Res<Difficulty>,
+    lives: Res<Lives>,
+) {
+    hud.0 = format!(
+        "Score: {}\nWave: {}  Difficulty: {:.0}%",
+        score.seconds(),
+        difficulty.wave + 1,
+        difficulty.level * 100.0
+    );
+    if lives.total > 1 {
+        hud.0 += &format!("\nHulls: {}/{}", lives.remaining, lives.total);
     }
 }
 
-fn spawn_game_over_menu(mut commands: Commands, asset_server: Res<AssetServer>){
+fn spawn_game_over_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    score: Res<Score>,
+    save: Res<SaveData>,
+){
+    let font = asset_server.load("fonts/Kenney Mini Square.ttf");
+    let best = save.best_score().unwrap_or(0).max(score.seconds());
+    commands.spawn(menu_root(GameState::GameOver)).with_children(|parent| {
+        parent.spawn(menu_text(format!("Score: {}  Best: {}", score.seconds(), best), &font, 33.0));
+        parent
+            .spawn(Node {
+                column_gap: MARGIN,
+                ..default()
+            })
+            .with_children(|row| {
+                row.spawn(state_button(GameState::Game))
+                    .with_child(menu_text("Again?", &font, 33.0));
+                row.spawn(state_button(GameState::Menu))
+                    .with_child(menu_text("Menu", &font, 33.0));
+            });
+    });
+}
+
+/// opens and closes the settings menu, escape always cancels a pending rebind
+fn toggle_settings_menu(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
+    asset_server: Res<AssetServer>,
+    state: Res<State<GameState>>,
+    menu: Option<Single<Entity, With<SettingsMenu>>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut swapped: ResMut<SwappedBinding>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    if rebinding.0.is_some() {
+        if keys.just_pressed(KeyCode::Escape) {
+            rebinding.0 = None;
+        }
+        return;
+    }
+    if !actions.just_pressed(Action::Settings) {
+        return;
+    }
+    swapped.0 = None;
+
+    match menu {
+        Some(menu) => {
+            commands.entity(*menu).despawn_recursive();
+            time.unpause();
+        }
+        None => {
+            let font = asset_server.load("fonts/Kenney Mini Square.ttf");
+            spawn_settings_menu(&mut commands, font, state.get().clone());
+            time.pause();
+        }
+    }
+}
+
+/// the settings menu belongs to the state it was opened in, leaving that
+/// state removes it and lets the game run again
+fn close_settings_menu(mut rebinding: ResMut<Rebinding>, mut time: ResMut<Time<Virtual>>) {
+    rebinding.0 = None;
+    time.unpause();
+}
+
+fn spawn_settings_menu(commands: &mut Commands, font: Handle<Font>, state: GameState) {
+    let text_font = TextFont {
+        font,
+        font_size: 18.0,
+        ..default()
+    };
+    let button_node = Node {
+        width: Val::Px(320.0),
+        height: Val::Px(32.0),
+        border: UiRect::all(Val::Px(3.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+
     commands
         .spawn((
-                   Node {
-                       width: Val::Percent(100.0),
-                       height: Val::Percent(100.0),
-                       align_items: AlignItems::Center,
-                       justify_content: JustifyContent::Center,
-                       ..default()
-                   },
-                    BackgroundColor(Color::BLACK),
+            SettingsMenu,
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+            GlobalZIndex(10),
+            // the menus underneath can't be clicked through the overlay
+            FocusPolicy::Block,
+            StateScoped(state),
         ))
         .with_children(|parent| {
+            parent.spawn((
+                Text::new("Settings"),
+                text_font.clone(),
+                TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                Node {
+                    margin: UiRect::bottom(MARGIN),
+                    ..default()
+                },
+            ));
+            for channel in VolumeChannel::ALL {
+                parent
+                    .spawn(Node {
+                        column_gap: MARGIN,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn((
+                            Text::new(channel.label()),
+                            text_font.clone(),
+                            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                            Node {
+                                width: Val::Px(200.0),
+                                ..default()
+                            },
+                        ));
+                        for (step, sign) in [(-0.1, "-"), (0.1, "+")] {
+                            if step > 0.0 {
+                                row.spawn((
+                                    VolumeLabel(channel),
+                                    Text::default(),
+                                    text_font.clone(),
+                                    TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                                    Node {
+                                        width: Val::Px(216.0),
+                                        justify_content: JustifyContent::Center,
+                                        ..default()
+                                    },
+                                ));
+                            }
+                            row.spawn((
+                                Button,
+                                VolumeButton { channel, step },
+                                Node {
+                                    width: Val::Px(40.0),
+                                    ..button_node.clone()
+                                },
+                                BorderColor(Color::BLACK),
+                                BackgroundColor(NORMAL_BUTTON),
+                            ))
+                            .with_child((
+                                Text::new(sign),
+                                text_font.clone(),
+                                TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                            ));
+                        }
+                    });
+            }
+            for action in Action::ALL {
+                parent
+                    .spawn(Node {
+                        column_gap: MARGIN,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn((
+                            Text::new(action.label()),
+                            text_font.clone(),
+                            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                            Node {
+                                width: Val::Px(200.0),
+                                ..default()
+                            },
+                        ));
+                        row.spawn((
+                            Button,
+                            RebindButton(action),
+                            button_node.clone(),
+                            BorderColor(Color::BLACK),
+                            BackgroundColor(NORMAL_BUTTON),
+                        ))
+                        .with_child((
+                            Text::default(),
+                            text_font.clone(),
+                            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                        ));
+                    });
+            }
             parent
                 .spawn((
                     Button,
+                    ResetBindingsButton,
                     Node {
-                        width: Val::Px(150.0),
-                        height: Val::Px(65.0),
-                        border: UiRect::all(Val::Px(5.0)),
-                        // horizontally center child text
-                        justify_content: JustifyContent::Center,
-                        // vertically center child text
-                        align_items: AlignItems::Center,
-                        ..default()
+                        margin: UiRect::top(MARGIN),
+                        ..button_node.clone()
                     },
                     BorderColor(Color::BLACK),
-                    BorderRadius::MAX,
                     BackgroundColor(NORMAL_BUTTON),
                 ))
                 .with_child((
-                    Text::new("Again?"),
-                    TextFont {
-                        font: asset_server.load("fonts/Kenney Mini Square.ttf"),
-                        font_size: 33.0,
-                        ..default()
-                    },
+                    Text::new("Reset to defaults"),
+                    text_font.clone(),
                     TextColor(Color::srgb(0.9, 0.9, 0.9)),
                 ));
+            parent.spawn((
+                BindingNotice,
+                Text::default(),
+                text_font.clone(),
+                TextColor(Color::srgb(0.9, 0.7, 0.3)),
+            ));
         });
-    }
\ No newline at end of file
+}
+
+fn settings_button_handler(
+    mut interaction_query: Query<
+        (
+            &Interaction,
+            &mut BackgroundColor,
+            &mut BorderColor,
+            Option<&RebindButton>,
+            Option<&VolumeButton>,
+        ),
+        (
+            Changed<Interaction>,
+            Or<(With<RebindButton>, With<ResetBindingsButton>, With<VolumeButton>)>,
+        ),
+    >,
+    mut rebinding: ResMut<Rebinding>,
+    mut swapped: ResMut<SwappedBinding>,
+    mut save: ResMut<SaveData>,
+) {
+    for (interaction, mut color, mut border_color, rebind, volume) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = PRESSED_BUTTON.into();
+                match (rebind, volume) {
+                    (Some(RebindButton(action)), _) => {
+                        rebinding.0 = Some(*action);
+                        swapped.0 = None;
+                    }
+                    (_, Some(VolumeButton { channel, step })) => {
+                        let value = save.settings.volume_mut(*channel);
+                        *value = ((*value + step) * 10.0).round().clamp(0.0, 10.0) / 10.0;
+                    }
+                    _ => {
+                        save.settings.bindings.reset();
+                        rebinding.0 = None;
+                        swapped.0 = None;
+                    }
+                }
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+                border_color.0 = Color::WHITE;
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+                border_color.0 = Color::BLACK;
+            }
+        }
+    }
+}
+
+fn update_binding_labels(
+    save: Res<SaveData>,
+    rebinding: Res<Rebinding>,
+    swapped: Res<SwappedBinding>,
+    buttons: Query<(&RebindButton, &Children)>,
+    added: Query<(), Added<RebindButton>>,
+    mut notice: Query<&mut Text, With<BindingNotice>>,
+    mut texts: Query<&mut Text, Without<BindingNotice>>,
+) {
+    if !save.is_changed() && !rebinding.is_changed() && !swapped.is_changed() && added.is_empty() {
+        return;
+    }
+    if let Ok(mut notice) = notice.get_single_mut() {
+        notice.0 = match swapped.0 {
+            Some((binding, other)) => format!("{binding} no longer does \"{}\"", other.label()),
+            None => String::new(),
+        };
+    }
+    for (RebindButton(action), children) in buttons.iter() {
+        let Some(mut text) = children.first().and_then(|child| texts.get_mut(*child).ok()) else {
+            continue;
+        };
+        text.0 = if rebinding.0 == Some(*action) {
+            "press a button...".to_string()
+        } else {
+            save.settings
+                .bindings
+                .get(*action)
+                .iter()
+                .map(|binding| binding.to_string())
+                .collect::<Vec<_>>()
+                .join(" / ")
+        };
+    }
+}
+
+fn update_volume_labels(
+    save: Res<SaveData>,
+    added: Query<(), Added<VolumeLabel>>,
+    mut labels: Query<(&VolumeLabel, &mut Text)>,
+) {
+    if !save.is_changed() && added.is_empty() {
+        return;
+    }
+    for (VolumeLabel(channel), mut text) in labels.iter_mut() {
+        text.0 = format!("{:.0}%", save.settings.volume(*channel) * 100.0);
+    }
+}
@@ -0,0 +1,262 @@
+use bevy::pbr::{DistanceFog, FogFalloff};
+use bevy::prelude::*;
+use bevy_rand::prelude::*;
+use bevy_water::WaterSettings;
+use rand::Rng;
+use serde::Deserialize;
+use crate::camera::PanOrbitState;
+use crate::config::ConfigAssets;
+use crate::daynight::TimeOfDay;
+use crate::level::{CurrentLevel, LevelList};
+use crate::GameState;
+
+pub struct WeatherPlugin;
+
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Weather>()
+            .add_systems(OnEnter(GameState::Game), reset_weather)
+            .add_systems(
+                Update,
+                (advance_weather, apply_weather)
+                    .chain()
+                    .run_if(in_state(GameState::Game)),
+            );
+    }
+}
+
+/// seconds it takes to blend from one weather into the next
+const TRANSITION_SECS: f32 = 8.0;
+/// range of seconds random weather lasts
+const RANDOM_DURATION: std::ops::Range<f32> = 40.0..90.0;
+/// share of the fog distance that is left at midnight
+const NIGHT_VISIBILITY: f32 = 0.4;
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WeatherKind {
+    #[default]
+    Calm,
+    Fog,
+    Storm,
+}
+
+/// Weather that sets in `at` seconds into a session, part of a level's data.
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct WeatherChange {
+    pub at: f32,
+    pub weather: WeatherKind,
+}
+
+/// Everything a weather changes, blended while the weather turns.
+#[derive(Clone, Copy, Debug)]
+pub struct WeatherProfile {
+    /// wave height of the water
+    pub amplitude: f32,
+    /// distance where fog starts and where it hides everything
+    pub fog_start: f32,
+    pub fog_end: f32,
+    pub fog_color: Color,
+    /// factor on sun and ambient light
+    pub ambient: f32,
+    /// factor on ship speed
+    pub ship_speed: f32,
+}
+
+impl WeatherKind {
+    pub fn profile(self) -> WeatherProfile {
+        match self {
+            WeatherKind::Calm => WeatherProfile {
+                amplitude: 0.7,
+                fog_start: 300.0,
+                fog_end: 1000.0,
+                fog_color: Color::srgba(0.7, 0.75, 0.8, 1.0),
+                ambient: 1.0,
+                ship_speed: 1.0,
+            },
+            WeatherKind::Fog => WeatherProfile {
+                amplitude: 0.4,
+                fog_start: 20.0,
+                fog_end: 120.0,
+                fog_color: Color::srgba(0.8, 0.8, 0.82, 1.0),
+                ambient: 0.7,
+                ship_speed: 0.9,
+            },
+            WeatherKind::Storm => WeatherProfile {
+                amplitude: 2.0,
+                fog_start: 60.0,
+                fog_end: 250.0,
+                fog_color: Color::srgba(0.25, 0.27, 0.3, 1.0),
+                ambient: 0.35,
+                ship_speed: 0.6,
+            },
+        }
+    }
+
+    /// weathers that can follow this one
+    fn neighbours(self) -> [WeatherKind; 2] {
+        match self {
+            WeatherKind::Calm => [WeatherKind::Fog, WeatherKind::Storm],
+            WeatherKind::Fog => [WeatherKind::Calm, WeatherKind::Storm],
+            WeatherKind::Storm => [WeatherKind::Calm, WeatherKind::Fog],
+        }
+    }
+}
+
+impl WeatherProfile {
+    fn lerp(&self, other: &WeatherProfile, t: f32) -> WeatherProfile {
+        WeatherProfile {
+            amplitude: self.amplitude.lerp(other.amplitude, t),
+            fog_start: self.fog_start.lerp(other.fog_start, t),
+            fog_end: self.fog_end.lerp(other.fog_end, t),
+            fog_color: self.fog_color.mix(&other.fog_color, t),
+            ambient: self.ambient.lerp(other.ambient, t),
+            ship_speed: self.ship_speed.lerp(other.ship_speed, t),
+        }
+    }
+}
+
+/// The weather state machine of the running session.
+#[derive(Resource, Debug)]
+pub struct Weather {
+    /// the weather we are coming from
+    pub current: WeatherKind,
+    /// the weather we are going to, equal to `current` when settled
+    pub target: WeatherKind,
+    /// 0 at the start of a transition, 1 once `target` has fully set in
+    blend: f32,
+    /// the profile shown when the transition started, which may itself be a blend
+    from: WeatherProfile,
+    /// no level schedule is left, the weather changes on its own
+    random: bool,
+    /// seconds since the session started
+    elapsed: f32,
+    /// when the next random change happens
+    next_random_change: f32,
+    /// level driven changes that have not happened yet, earliest last
+    schedule: Vec<WeatherChange>,
+    /// the blended profile other systems should read
+    pub profile: WeatherProfile,
+}
+
+impl Default for Weather {
+    fn default() -> Self {
+        Self {
+            current: WeatherKind::Calm,
+            target: WeatherKind::Calm,
+            blend: 1.0,
+            from: WeatherKind::Calm.profile(),
+            random: true,
+            elapsed: 0.0,
+            next_random_change: RANDOM_DURATION.start,
+            schedule: Vec::new(),
+            profile: WeatherKind::Calm.profile(),
+        }
+    }
+}
+
+impl Weather {
+    /// how much of `kind` is in the current weather, 0..1
+    pub fn share(&self, kind: WeatherKind) -> f32 {
+        let from = if self.current == kind { 1.0 - self.blend } else { 0.0 };
+        let to = if self.target == kind { self.blend } else { 0.0 };
+        from + to
+    }
+
+    /// weather for a session, following the given level changes if there are any
+    pub fn with_schedule(mut schedule: Vec<WeatherChange>) -> Self {
+        schedule.sort_by(|a, b| b.at.total_cmp(&a.at));
+        Self {
+            random: schedule.is_empty(),
+            schedule,
+            ..default()
+        }
+    }
+
+    /// starts blending from what is shown right now towards `weather`
+    pub fn change_to(&mut self, weather: WeatherKind) {
+        if weather == self.target {
+            return;
+        }
+        self.from = self.profile;
+        self.current = self.target;
+        self.target = weather;
+        self.blend = 0.0;
+    }
+}
+
+fn reset_weather(
+    mut commands: Commands,
+    config: Res<ConfigAssets>,
+    level_lists: Res<Assets<LevelList>>,
+    current_level: Res<CurrentLevel>,
+) {
+    let schedule = current_level
+        .get(&config, &level_lists)
+        .map(|level| level.weather.clone())
+        .unwrap_or_default();
+    commands.insert_resource(Weather::with_schedule(schedule));
+}
+
+fn advance_weather(
+    time: Res<Time>,
+    mut weather: ResMut<Weather>,
+    mut rng: GlobalEntropy<WyRand>,
+) {
+    weather.elapsed += time.delta_secs();
+
+    if weather.random {
+        if weather.elapsed >= weather.next_random_change {
+            let next = weather.target.neighbours()[rng.gen_range(0..2)];
+            weather.change_to(next);
+            weather.next_random_change = weather.elapsed + rng.gen_range(RANDOM_DURATION);
+        }
+    } else {
+        while weather.schedule.last().is_some_and(|change| change.at <= weather.elapsed) {
+            if let Some(change) = weather.schedule.pop() {
+                weather.change_to(change.weather);
+            }
+        }
+        // the level's last weather gets a full spell before random weather takes over
+        if weather.schedule.is_empty() {
+            weather.random = true;
+            weather.next_random_change = weather.elapsed + rng.gen_range(RANDOM_DURATION);
+        }
+    }
+
+    weather.blend = (weather.blend + time.delta_secs() / TRANSITION_SECS).min(1.0);
+    weather.profile = weather.from.lerp(&weather.target.profile(), weather.blend);
+}
+
+fn apply_weather(
+    mut commands: Commands,
+    weather: Res<Weather>,
+    time_of_day: Res<TimeOfDay>,
+    mut water_settings: ResMut<WaterSettings>,
+    cameras: Query<Entity, (With<PanOrbitState>, Without<DistanceFog>)>,
+    mut fogs: Query<&mut DistanceFog>,
+) {
+    let profile = &weather.profile;
+    // bevy_water has no wave speed setting, storms roughen the sea through the amplitude
+    if water_settings.amplitude != profile.amplitude {
+        water_settings.amplitude = profile.amplitude;
+    }
+
+    // it is harder to see far at night
+    let visibility = NIGHT_VISIBILITY.lerp(1.0, time_of_day.daylight());
+    let fog_color = profile.fog_color.mix(&Color::BLACK, 0.8 * (1.0 - time_of_day.daylight()));
+    let falloff = FogFalloff::Linear {
+        start: profile.fog_start * visibility,
+        end: profile.fog_end * visibility,
+    };
+    for camera in cameras.iter() {
+        commands.entity(camera).insert(DistanceFog {
+            color: fog_color,
+            falloff: falloff.clone(),
+            ..default()
+        });
+    }
+    for mut fog in fogs.iter_mut() {
+        fog.color = fog_color;
+        fog.falloff = falloff.clone();
+    }
+}
@@ -0,0 +1,171 @@
+use bevy::pbr::NotShadowCaster;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use rand::prelude::*;
+use crate::collision::{Collider, CollisionEvent, Obstacle};
+use crate::map::WATER_HEIGHT;
+use crate::ship::{PathFollow, ShipRoot};
+use crate::GameState;
+
+pub struct WreckPlugin;
+
+impl Plugin for WreckPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Wrecks>()
+            .init_resource::<WreckLook>()
+            .add_systems(
+                Update,
+                (sink_crashed_ships, sink_ships, expire_wrecks)
+                    .chain()
+                    .run_if(in_state(GameState::Game)),
+            );
+    }
+}
+
+/// seconds from the crash until a ship is under water
+const SINK_SECS: f32 = 4.0;
+/// how far the fastest flooding probe goes down
+const SINK_DEPTH: f32 = 6.0;
+
+/// Whether sunk ships leave a wreck behind, only when the game
+/// goes on after a crash.
+#[derive(Resource, Debug)]
+pub struct Wrecks {
+    pub enabled: bool,
+    /// seconds a wreck stays before it breaks up
+    pub secs: f32,
+}
+
+impl Default for Wrecks {
+    fn default() -> Self {
+        Wrecks { enabled: false, secs: 30.0 }
+    }
+}
+
+/// A crashed ship taking on water. It has left its route and lost its
+/// collider, the hull goes down on its buoyancy probes and is removed once under.
+#[derive(Component)]
+pub struct Sinking {
+    timer: Timer,
+    /// how fast the front, back left and back right probe flood, 0..1
+    rates: Vec3,
+    /// the ship's collider, handed to the wreck
+    collider: Rect,
+}
+
+impl Sinking {
+    fn new(collider: Rect) -> Self {
+        let mut rng = rand::thread_rng();
+        Sinking {
+            timer: Timer::from_seconds(SINK_SECS, TimerMode::Once),
+            rates: Vec3::new(rng.gen_range(0.5..1.0), rng.gen_range(0.5..1.0), rng.gen_range(0.5..1.0)),
+            collider,
+        }
+    }
+
+    /// depth below the waves of each probe, slow at first and then faster
+    pub fn flooding(&self) -> Vec3 {
+        let progress = self.timer.fraction();
+        self.rates * SINK_DEPTH * progress * progress
+    }
+}
+
+/// What is left of a sunk ship, other ships have to go around it until it breaks up.
+#[derive(Component)]
+struct Wreck(Timer);
+
+/// Shared mesh and material of the wrecks.
+#[derive(Resource)]
+struct WreckLook {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+impl FromWorld for WreckLook {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world.resource_mut::<Assets<Mesh>>().add(Cuboid::new(1.0, 1.0, 1.0));
+        let material = world.resource_mut::<Assets<StandardMaterial>>().add(StandardMaterial {
+            base_color: Color::srgb(0.22, 0.17, 0.13),
+            perceptual_roughness: 0.95,
+            ..default()
+        });
+        WreckLook { mesh, material }
+    }
+}
+
+/// both ships of a crash start to sink
+fn sink_crashed_ships(
+    mut commands: Commands,
+    mut collisions: EventReader<CollisionEvent>,
+    ships: Query<&Collider, (With<ShipRoot>, Without<Sinking>)>,
+) {
+    let mut sunk = HashSet::new();
+    for ev in collisions.read() {
+        for entity in [ev.entity_a, ev.entity_b] {
+            let Ok(collider) = ships.get(entity) else {
+                continue;
+            };
+            if !sunk.insert(entity) {
+                continue;
+            }
+            commands
+                .entity(entity)
+                .remove::<(PathFollow, Collider)>()
+                .insert(Sinking::new(collider.rect));
+        }
+    }
+}
+
+fn sink_ships(
+    mut commands: Commands,
+    time: Res<Time>,
+    wrecks: Res<Wrecks>,
+    look: Res<WreckLook>,
+    mut ships: Query<(Entity, &mut Sinking, &Transform)>,
+) {
+    for (entity, mut sinking, transform) in ships.iter_mut() {
+        if !sinking.timer.tick(time.delta()).finished() {
+            continue;
+        }
+        commands.entity(entity).despawn_recursive();
+        if !wrecks.enabled {
+            continue;
+        }
+
+        // colliders reach from the entity along +x and +z
+        let size = sinking.collider.size();
+        let mut rng = rand::thread_rng();
+        let tilt = Quat::from_euler(
+            EulerRot::YXZ,
+            rng.gen_range(-0.2..0.2),
+            rng.gen_range(-0.3..0.3),
+            rng.gen_range(-0.3..0.3),
+        );
+        commands
+            .spawn((
+                Name::new("Wreck"),
+                Wreck(Timer::from_seconds(wrecks.secs, TimerMode::Once)),
+                Obstacle,
+                Collider { rect: sinking.collider },
+                Transform::from_translation(transform.translation.with_y(WATER_HEIGHT)),
+                Visibility::default(),
+                StateScoped(GameState::Game),
+            ))
+            .with_child((
+                Mesh3d(look.mesh.clone()),
+                MeshMaterial3d(look.material.clone()),
+                Transform::from_xyz(size.x / 2.0, 0.0, size.y / 2.0)
+                    .with_rotation(tilt)
+                    .with_scale(Vec3::new(size.x * 0.8, 1.5, size.y * 0.8)),
+                NotShadowCaster,
+            ));
+    }
+}
+
+fn expire_wrecks(mut commands: Commands, time: Res<Time>, mut wrecks: Query<(Entity, &mut Wreck)>) {
+    for (entity, mut wreck) in wrecks.iter_mut() {
+        if wreck.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
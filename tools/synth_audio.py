@@ -0,0 +1,273 @@
+#!/usr/bin/env python3
+"""Synthesizes the sound effects and music in assets/audio.
+
+Everything is generated from plain oscillators and seeded noise, so the
+files can be rebuilt at any time with `python3 tools/synth_audio.py`.
+"""
+
+import math
+import random
+import struct
+import wave
+from pathlib import Path
+
+RATE = 22050
+OUT = Path(__file__).resolve().parent.parent / "assets" / "audio"
+
+# music: 8 bars of 4/4 at 120 bpm, every track loops after the same 16 seconds
+# so the stems of the game music stay in sync
+BPM = 120
+BEAT = 60 / BPM
+BARS = 8
+LOOP = BARS * 4 * BEAT
+# A minor, F, C, G, two bars each
+CHORDS = [(57, 60, 64), (53, 57, 60), (48, 52, 55), (55, 59, 62)]
+
+
+def midi(note):
+    return 440.0 * 2 ** ((note - 69) / 12)
+
+
+def silence(secs):
+    return [0.0] * int(secs * RATE)
+
+
+def envelope(i, length, attack, release):
+    t = i / RATE
+    end = length / RATE
+    gain = min(1.0, t / attack) if attack > 0 else 1.0
+    if release > 0:
+        gain *= min(1.0, (end - t) / release)
+    return max(0.0, gain)
+
+
+def add(buffer, start, samples, gain=1.0, wrap=False):
+    """mixes `samples` into `buffer`, wrapping around the end for loops"""
+    offset = int(start * RATE)
+    for i, sample in enumerate(samples):
+        index = offset + i
+        if wrap:
+            index %= len(buffer)
+        elif index >= len(buffer):
+            break
+        buffer[index] += sample * gain
+
+
+def lowpass(samples, cutoff, looped=False):
+    alpha = 1 - math.exp(-2 * math.pi * cutoff / RATE)
+    out, y = [], 0.0
+    if looped:
+        # start from where the end of the loop leaves the filter
+        for sample in samples:
+            y += alpha * (sample - y)
+    for sample in samples:
+        y += alpha * (sample - y)
+        out.append(y)
+    return out
+
+
+def tone(freq, secs, harmonics=(1.0,), attack=0.01, release=0.05, decay=0.0, vibrato=0.0):
+    length = int(secs * RATE)
+    out = []
+    phase = 0.0
+    for i in range(length):
+        t = i / RATE
+        f = freq * (1 + vibrato * math.sin(2 * math.pi * 5 * t))
+        phase += 2 * math.pi * f / RATE
+        value = sum(h * math.sin(phase * (n + 1)) for n, h in enumerate(harmonics))
+        gain = envelope(i, length, attack, release)
+        if decay > 0:
+            gain *= math.exp(-t / decay)
+        out.append(value * gain)
+    return out
+
+
+def noise(secs, rng, decay, cutoff):
+    length = int(secs * RATE)
+    raw = [
+        rng.uniform(-1, 1) * envelope(i, length, 0.002, 0.0) * math.exp(-i / RATE / decay)
+        for i in range(length)
+    ]
+    return lowpass(raw, cutoff)
+
+
+def normalize(samples, peak):
+    top = max(abs(s) for s in samples) or 1.0
+    return [s * peak / top for s in samples]
+
+
+def write(path, samples):
+    path = OUT / path
+    path.parent.mkdir(parents=True, exist_ok=True)
+    with wave.open(str(path), "wb") as file:
+        file.setnchannels(1)
+        file.setsampwidth(2)
+        file.setframerate(RATE)
+        frames = b"".join(
+            struct.pack("<h", int(max(-1.0, min(1.0, s)) * 32767)) for s in samples
+        )
+        file.writeframes(frames)
+    print(f"wrote {path.relative_to(OUT.parent.parent)}")
+
+
+# sound effects
+
+
+def horn():
+    # two low reeds a fifth apart, slightly wobbling
+    harmonics = (1.0, 0.6, 0.45, 0.3, 0.2, 0.1)
+    low = tone(98, 1.6, harmonics, attack=0.12, release=0.35, vibrato=0.004)
+    high = tone(147, 1.6, harmonics, attack=0.15, release=0.35, vibrato=0.003)
+    mixed = [a + 0.7 * b for a, b in zip(low, high)]
+    return normalize(lowpass(mixed, 900), 0.8)
+
+
+def crash(rng):
+    out = silence(1.4)
+    add(out, 0, noise(1.4, rng, 0.25, 2500), 0.8)
+    add(out, 0.02, noise(1.2, rng, 0.5, 500), 0.6)
+    # the hulls groaning, a thud falling in pitch
+    thud = []
+    phase = 0.0
+    for i in range(int(0.6 * RATE)):
+        t = i / RATE
+        phase += 2 * math.pi * (70 - 40 * t) / RATE
+        thud.append(math.sin(phase) * math.exp(-t / 0.18))
+    add(out, 0, thud, 1.0)
+    return normalize(out, 0.9)
+
+
+def chime():
+    out = silence(1.6)
+    for start, note in [(0.0, 81), (0.18, 88)]:
+        freq = midi(note)
+        bell = [
+            a + 0.4 * b + 0.2 * c
+            for a, b, c in zip(
+                tone(freq, 1.4, attack=0.002, release=0.1, decay=0.45),
+                tone(freq * 2.76, 1.4, attack=0.002, release=0.1, decay=0.2),
+                tone(freq * 5.4, 1.4, attack=0.002, release=0.1, decay=0.08),
+            )
+        ]
+        add(out, start, bell, 1.0)
+    return normalize(out, 0.7)
+
+
+def click():
+    out = tone(1800, 0.04, (1.0, 0.3), attack=0.0005, release=0.005, decay=0.008)
+    return normalize(out, 0.6)
+
+
+# music
+
+
+def chord_at(bar):
+    return CHORDS[(bar // 2) % len(CHORDS)]
+
+
+def pad(buffer, octave, gain, cutoff):
+    """sustained chords, each fading into the next across the loop point"""
+    voices = silence(LOOP)
+    for bar in range(0, BARS, 2):
+        for note in chord_at(bar):
+            for detune in (-0.003, 0.003):
+                freq = midi(note + 12 * octave) * (1 + detune)
+                add(
+                    voices,
+                    bar * 4 * BEAT,
+                    tone(freq, 8 * BEAT + 1.0, (1.0, 0.35, 0.15), attack=1.2, release=1.5),
+                    1.0,
+                    wrap=True,
+                )
+    add(buffer, 0, lowpass(voices, cutoff, looped=True), gain)
+
+
+def arpeggio(buffer, gain, per_beat, octave):
+    step = BEAT / per_beat
+    for index in range(int(LOOP / step)):
+        bar = int(index * step / (4 * BEAT))
+        chord = chord_at(bar)
+        note = chord[index % len(chord)] + 12 * octave + (12 if index % 8 >= 6 else 0)
+        pluck = tone(midi(note), 0.6, (1.0, 0.25, 0.1), attack=0.005, release=0.1, decay=0.15)
+        add(buffer, index * step, pluck, gain, wrap=True)
+
+
+def bass(buffer, gain):
+    for beat in range(BARS * 4):
+        root = chord_at(beat // 4)[0] - 12
+        note = root + (7 if beat % 4 == 3 else 0)
+        pluck = tone(midi(note), BEAT * 0.9, (1.0, 0.5, 0.2), attack=0.01, release=0.08, decay=0.4)
+        add(buffer, beat * BEAT, pluck, gain, wrap=True)
+
+
+def drums(buffer, rng):
+    kick = []
+    phase = 0.0
+    for i in range(int(0.35 * RATE)):
+        t = i / RATE
+        phase += 2 * math.pi * (40 + 90 * math.exp(-t / 0.04)) / RATE
+        kick.append(math.sin(phase) * math.exp(-t / 0.12))
+    for beat in range(BARS * 4):
+        start = beat * BEAT
+        if beat % 2 == 0:
+            add(buffer, start, kick, 1.0, wrap=True)
+        else:
+            add(buffer, start, noise(0.25, rng, 0.07, 4000), 0.7, wrap=True)
+            add(buffer, start, tone(190, 0.15, attack=0.001, release=0.02, decay=0.05), 0.4, wrap=True)
+        for half in range(2):
+            # noise with its lows taken out
+            bright, dull = noise(0.06, rng, 0.015, 9000), noise(0.06, rng, 0.015, 2500)
+            hat = [a - b for a, b in zip(bright, dull)]
+            add(buffer, start + half * BEAT / 2, hat, 0.5 if half else 0.3, wrap=True)
+
+
+def calm_music():
+    out = silence(LOOP)
+    pad(out, 0, 1.0, 1200)
+    arpeggio(out, 0.35, 1, 1)
+    return normalize(out, 0.6)
+
+
+def base_music():
+    out = silence(LOOP)
+    pad(out, 0, 0.6, 1500)
+    bass(out, 0.8)
+    arpeggio(out, 0.4, 2, 1)
+    return normalize(out, 0.6)
+
+
+def drum_stem(rng):
+    out = silence(LOOP)
+    drums(out, rng)
+    return normalize(out, 0.6)
+
+
+def string_stem():
+    out = silence(LOOP)
+    voices = silence(LOOP)
+    # bowed strings an octave up, a saw-like stack swelling over each chord
+    saw = tuple(1.0 / n for n in range(1, 9))
+    for bar in range(0, BARS, 2):
+        for note in chord_at(bar):
+            for detune in (-0.004, 0.0, 0.004):
+                freq = midi(note + 12) * (1 + detune)
+                string = tone(freq, 8 * BEAT + 0.8, saw, attack=0.6, release=0.8, vibrato=0.003)
+                add(voices, bar * 4 * BEAT, string, 1.0, wrap=True)
+    add(out, 0, lowpass(voices, 2200, looped=True), 1.0)
+    return normalize(out, 0.5)
+
+
+def main():
+    rng = random.Random(2025)
+    write("sfx/horn.wav", horn())
+    write("sfx/crash.wav", crash(rng))
+    write("sfx/chime.wav", chime())
+    write("sfx/click.wav", click())
+    write("music/harbour_calm.wav", calm_music())
+    write("music/harbour.wav", base_music())
+    write("music/harbour_drums.wav", drum_stem(rng))
+    write("music/harbour_strings.wav", string_stem())
+
+
+if __name__ == "__main__":
+    main()